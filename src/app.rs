@@ -1,20 +1,50 @@
+use std::collections::{BTreeMap, BTreeSet};
 use std::error;
+use std::fs;
 use std::ops::Div;
-
-use crossterm::event::{KeyCode, KeyEvent};
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use polars::datatypes::DataType;
+use polars::frame::DataFrame;
+use polars::io::SerWriter;
+use polars::prelude::{CsvWriter, InProcessQuery, NamedFrom, QuoteStyle};
+use polars::series::Series;
 use ratatui::layout::{Constraint, Layout};
+use ratatui::style::Color;
 use ratatui::Frame;
 use status_bar::{StatusBar, StatusBarState};
 use tabular::{Tabular, TabularType};
 
+use crate::args::AppTheme;
+use crate::clipboard::Clipboard;
 use crate::command::{CommandRegistery, Commands};
 use crate::keybind::{Action, Keybind};
+use crate::reader::{read_parquet_metadata, resolve_path, sheet_table_name, ReadOptions};
 use crate::sql::SqlBackend;
-use crate::theme::Styler;
+use crate::theme::{Argonaut, Monokai, Styler, Terminal};
+use crate::utils::{
+    any_value_into_string, cast_all, concat_frames, data_frame_to_tsv, distinct_values,
+    filter_value_at_end, group_aggregate, join_frames, parse_dtype, pivot_table, prefix_range,
+    rank_series, series_to_list, CopyFormat, FilterValue, ListStyle,
+};
+use crate::widget::{
+    CommandPalette, CommandPaletteState, Completions, CopyMenu, CopyMenuState, MessagePopup,
+    MessagePopupState, TextSelect, TextSelectState, Welcome,
+};
 
 pub mod status_bar;
 pub mod tabular;
 
+/// Number of values above which copying a column asks for confirmation.
+const COPY_WARN_ROWS: usize = 100_000;
+
+/// Number of matches listed by `:findall` at most.
+const SEARCH_MAX_MATCHES: usize = 10_000;
+
+/// Number of filter value completions shown at once.
+const COMPLETION_ROWS: usize = 8;
+
 /// Application result type.
 pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 
@@ -22,23 +52,77 @@ pub type AppResult<T> = std::result::Result<T, Box<dyn error::Error>>;
 pub struct Tabs {
     tabulars: Vec<Tabular>,
     idx: usize,
+    scrolloff: usize,
+    column_widths: Vec<(String, u16)>,
+    date_format: Option<String>,
 }
 
 pub struct App {
     tabs: Tabs,
     status_bar: StatusBar,
     sql: SqlBackend,
+    read_options: ReadOptions,
     exec_table: CommandRegistery,
     keybindings: Keybind,
+    clipboard: Clipboard,
+    palette: Option<CommandPaletteState>,
+    text_select: Option<TextSelectState>,
+    copy_menu: Option<CopyMenuState>,
+    message: Option<MessagePopupState>,
+    completion: Option<Completion>,
+    distinct: Option<(String, Vec<String>)>,
+    pending: Option<AppAction>,
+    query: Option<RunningQuery>,
+    cancelled: Vec<InProcessQuery>,
+    derived: BTreeSet<String>,
+    macros: BTreeMap<String, Vec<KeyEvent>>,
+    recording: Option<Recording>,
+    last_macro: Option<String>,
+    replaying: bool,
+    confirm: bool,
+    watch: bool,
+    show_path: bool,
     running: bool,
 }
 
+/// Values of the column compared at the end of a filter prompt.
+struct Completion {
+    prompt: String,
+    value: FilterValue,
+    is_str: bool,
+    values: Vec<String>,
+    count: usize,
+    common: String,
+}
+
+/// Macro being recorded: the keys of the finished interactions, and those of
+/// the one in progress, e.g. a command being typed, kept apart so that the
+/// keys typing `:macro stop` are left out.
+struct Recording {
+    name: String,
+    keys: Vec<KeyEvent>,
+    pending: Vec<KeyEvent>,
+}
+
+/// Query running in the background, with the type of the tab its result
+/// opens in, or none to replace the selected tab's data frame.
+struct RunningQuery {
+    handle: InProcessQuery,
+    tabular_type: Option<TabularType>,
+}
+
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
 pub enum AppState {
     Empty,
     Table,
     Sheet,
     Command,
+    Palette,
+    TextSelect,
+    CopyMenu,
+    Message,
+    Confirm,
+    Running,
     Error,
 }
 
@@ -46,12 +130,20 @@ pub enum AppState {
 pub enum AppAction {
     StatusBarStats,
     StatusBarCommand(String),
+    CommandRun(String),
     StatausBarError(String),
+    StatusBarTogglePath,
     TabularTableView,
     TabularSheetView,
     TabularSwitchView,
     SqlQuery(String),
     SqlSchema,
+    SqlExplain(String),
+    SqlMetadata,
+    SqlSetVariable(String, String),
+    SqlUnsetVariable(String),
+    SqlVariables,
+    ErrorLog,
     TabularGoto(usize),
     TabularGotoFirst,
     TabularGotoLast,
@@ -62,20 +154,78 @@ pub enum AppAction {
     TabularGoDown(usize),
     TabularGoDownHalfPage,
     TabularGoDownFullPage,
+    TabularGoLeft(usize),
+    TabularGoRight(usize),
+    TabularToggleColumn,
+    TabularToggleBar(String),
+    TabularColumnWidth(String, Option<u16>),
+    TabularDateFormat(Option<String>, Option<String>),
+    TabularHighlight(String, String, String, Color),
+    TabularResetHighlights(Option<String>),
+    TabularShowAllColumns,
+    TabularToggleThousands,
+    TabularCycleBoolGlyphs,
+    TabularCycleIntBase,
+    TabularToggleFooter,
+    TabularToggleTotals,
+    TabularToggleCompare,
+    TabularToggleGutter,
+    TabularToggleDense,
+    TabularToggleMissing,
+    TabularCycleHeaderMode,
+    TabularCopyColumn(ListStyle, bool),
+    TabularCopyColumnName,
+    TabularCopyView(bool),
+    TabularExport(PathBuf, u8, QuoteStyle),
+    TabularToggleBookmark,
+    TabularGotoNextBookmark,
+    TabularGotoPrevBookmark,
     SheetScrollUp,
     SheetScrollDown,
     TabularReset,
+    TabularLoadFull,
     TabularSelect(String),
     TabularOrder(String),
     TabularFilter(String),
+    TabularExplode(String),
+    TabularDropNulls(Vec<String>),
+    TabularDropColumns(Vec<String>),
+    TabularFilterSelectedCell,
+    TabularSample(usize, Option<u64>),
+    TabularReverse,
+    TabularRowNumber(String),
+    TabularRank(String, bool, String),
+    TabularCastAll(String),
+    TabularGroup(Vec<String>, String, Option<String>),
+    TabularPivot(String, String, String, String),
+    TabularGotoColumn(String),
+    TabularFind(String),
+    TabularFindNext,
+    TabularFindPrev,
+    TabularRenameColumn(String, String),
     TabNew(String),
+    TabOpen(PathBuf, Vec<String>),
+    TabSearch(String),
+    TabGotoMatch,
+    TabConcat(Vec<String>),
+    TabJoin(String, String, String, String, String),
     TabSelect(usize),
+    TabSelectLast,
     TabRemove(usize),
     TabRemoveSelected,
     TabSelectedPrev,
     TabSelectedNext,
     TabRemoveOrQuit,
     TabRename(usize, String),
+    TabTheme(Option<AppTheme>),
+    MacroRecord(String),
+    MacroStop,
+    MacroRun(String),
+    MacroRunLast,
+    MacroList,
+    PaletteShow,
+    TextSelectShow,
+    CopyMenuShow,
     Help,
     Quit,
 }
@@ -92,44 +242,224 @@ impl App {
             tabs,
             status_bar,
             sql,
+            read_options: ReadOptions::default(),
             exec_table,
             keybindings: key_bind,
+            clipboard: Clipboard::default(),
+            palette: None,
+            text_select: None,
+            copy_menu: None,
+            message: None,
+            completion: None,
+            distinct: None,
+            pending: None,
+            query: None,
+            cancelled: Vec::new(),
+            derived: BTreeSet::new(),
+            macros: BTreeMap::new(),
+            recording: None,
+            last_macro: None,
+            replaying: false,
+            confirm: true,
+            watch: false,
+            show_path: false,
             running: true,
         }
     }
 
+    /// Sets whether destructive actions, such as closing a tab, ask for
+    /// confirmation first.
+    pub fn with_confirm(mut self, confirm: bool) -> Self {
+        self.confirm = confirm;
+        self
+    }
+
+    /// Sets the options files opened with `:open` are read with.
+    pub fn with_read_options(mut self, read_options: ReadOptions) -> Self {
+        self.read_options = read_options;
+        self
+    }
+
+    /// Sets whether tabs are reloaded when their source file is modified.
+    pub fn with_watch(mut self, watch: bool) -> Self {
+        self.watch = watch;
+        self
+    }
+
     pub fn running(&self) -> bool {
         self.running
     }
 
     pub fn tick(&mut self) -> AppResult<()> {
         self.tabs.selected_mut().map(|tab| tab.tick());
+        // Cancelled queries are kept until they stop, as their thread panics
+        // if the handle is dropped before it sends the result.
+        self.cancelled.retain(|handle| handle.fetch().is_none());
+        if let Err(error) = self.fetch_query(false) {
+            self.status_bar.show_error(error)?;
+        }
+        if self.watch {
+            if let Err(error) = self.reload_modified() {
+                self.status_bar.show_error(error)?;
+            }
+        }
         self.status_bar.tick()
     }
 
+    /// Starts running the query, its variables already substituted, in the
+    /// background, its result opening in a new tab of the type or replacing
+    /// the selected tab's data frame.
+    fn start_query(&mut self, query: &str, tabular_type: Option<TabularType>) -> AppResult<()> {
+        let handle = self.sql.execute_concurrently(query)?;
+        self.await_query(handle, tabular_type)
+    }
+
+    /// Starts running the query on the selected tab's data frame, as table
+    /// `df`, in the background, its result replacing the data frame.
+    fn start_tab_query(&mut self, query: &str) -> AppResult<()> {
+        let Some(tab) = self.tabs.selected() else {
+            return Ok(());
+        };
+        let mut sql = SqlBackend::new();
+        sql.register("df", tab.data_frame().clone(), "".into());
+        let handle = sql.execute_concurrently(query)?;
+        self.await_query(handle, None)
+    }
+
+    /// Shows the query as running until its result is fetched.
+    fn await_query(
+        &mut self,
+        handle: InProcessQuery,
+        tabular_type: Option<TabularType>,
+    ) -> AppResult<()> {
+        self.query = Some(RunningQuery {
+            handle,
+            tabular_type,
+        });
+        self.status_bar.show_running("Running query...")
+    }
+
+    /// Applies the result of the background query, if it has finished or,
+    /// when waiting, once it does.
+    fn fetch_query(&mut self, wait: bool) -> AppResult<()> {
+        let Some(result) = self.query.as_ref().and_then(|query| {
+            if wait {
+                Some(query.handle.fetch_blocking())
+            } else {
+                query.handle.fetch()
+            }
+        }) else {
+            return Ok(());
+        };
+        let tabular_type = self.query.take().and_then(|query| query.tabular_type);
+        self.status_bar.show_info()?;
+        let df = match result {
+            Ok(df) => df,
+            Err(error) => {
+                self.message = Some(MessagePopupState::new("Query failed", error.to_string()));
+                return self.status_bar.show_error(error);
+            }
+        };
+        if let Some(tabular_type) = tabular_type {
+            self.tabs.add(Tabular::new(df, tabular_type))?;
+            self.tabs.select_last()
+        } else if let Some(tab) = self.tabs.selected_mut() {
+            tab.set_data_frame(df)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Reloads the tabs whose source file was modified since it was last
+    /// checked, keeping their selected row.
+    fn reload_modified(&mut self) -> AppResult<()> {
+        for tab in self.tabs.iter_mut() {
+            let name = match tab.tabular_type() {
+                TabularType::Name(name) if tab.read_options().is_some() => name.to_owned(),
+                _ => continue,
+            };
+            let Some(path) = self.sql.path(&name).cloned() else {
+                continue;
+            };
+            let Ok(modified) = fs::metadata(&path).and_then(|meta| meta.modified()) else {
+                continue;
+            };
+            if !tab.update_modified(modified) {
+                continue;
+            }
+            let read_options = tab.read_options().ok_or("Read options not found")?;
+            let df = if tab.is_preview() {
+                read_options.read_preview(&path)?
+            } else {
+                read_options.read(&path)?
+            };
+            self.sql.update(&name, df.clone())?;
+            let selected = tab.selected();
+            tab.set_data_frame(df)?;
+            tab.select(selected)?;
+            tab.mark_loaded();
+        }
+        Ok(())
+    }
+
     pub fn quit(&mut self) -> AppResult<()> {
         self.running = false;
         Ok(())
     }
 
     pub fn infer_state(&self) -> AppState {
+        if self.query.is_some() {
+            return AppState::Running;
+        }
+        if self.pending.is_some() {
+            return AppState::Confirm;
+        }
+        if self.palette.is_some() {
+            return AppState::Palette;
+        }
+        if self.text_select.is_some() {
+            return AppState::TextSelect;
+        }
+        if self.copy_menu.is_some() {
+            return AppState::CopyMenu;
+        }
+        if self.message.is_some() {
+            return AppState::Message;
+        }
         match (
             self.tabs.selected().map(Tabular::state),
             self.status_bar.state(),
         ) {
-            (Some(tabular::TabularState::Table), StatusBarState::Info) => AppState::Table,
+            (
+                Some(tabular::TabularState::Table),
+                StatusBarState::Info | StatusBarState::Message(_),
+            ) => AppState::Table,
             (Some(tabular::TabularState::Table), StatusBarState::Error(_)) => AppState::Error,
             (Some(tabular::TabularState::Table), StatusBarState::Prompt(_)) => AppState::Command,
-            (Some(tabular::TabularState::Sheet(_)), StatusBarState::Info) => AppState::Sheet,
+            (
+                Some(tabular::TabularState::Sheet(_)),
+                StatusBarState::Info | StatusBarState::Message(_),
+            ) => AppState::Sheet,
             (Some(tabular::TabularState::Sheet(_)), StatusBarState::Error(_)) => AppState::Error,
             (Some(tabular::TabularState::Sheet(_)), StatusBarState::Prompt(_)) => AppState::Command,
-            (None, StatusBarState::Info) => AppState::Empty,
+            (None, StatusBarState::Info | StatusBarState::Message(_)) => AppState::Empty,
             (None, StatusBarState::Error(_)) => AppState::Error,
             (None, StatusBarState::Prompt(_)) => AppState::Command,
+            (_, StatusBarState::Confirm(_)) => AppState::Confirm,
+            (_, StatusBarState::Running(..)) => AppState::Running,
         }
     }
 
     pub fn draw<Theme: Styler>(&mut self, frame: &mut Frame) -> AppResult<()> {
+        match self.tabs.selected().and_then(Tabular::theme) {
+            Some(AppTheme::Monokai) => self.draw_themed::<Monokai>(frame),
+            Some(AppTheme::Argonaut) => self.draw_themed::<Argonaut>(frame),
+            Some(AppTheme::Terminal) => self.draw_themed::<Terminal>(frame),
+            None => self.draw_themed::<Theme>(frame),
+        }
+    }
+
+    fn draw_themed<Theme: Styler>(&mut self, frame: &mut Frame) -> AppResult<()> {
         let layout =
             Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).split(frame.area());
 
@@ -137,8 +467,65 @@ impl App {
         let state = self.infer_state();
         if let Some(tab) = self.tabs.selected_mut() {
             tab.render::<Theme>(frame, layout[0], matches!(state, AppState::Table))?;
+        } else {
+            frame.render_widget(
+                Welcome::new(Theme::sheet_value(), Theme::table_header_cell(0)),
+                layout[0],
+            );
+        }
+        if let Some(palette) = self.palette.as_mut() {
+            frame.render_stateful_widget(
+                CommandPalette::new(
+                    Theme::sheet_block(),
+                    Theme::status_bar_prompt(),
+                    Theme::table_highlight(),
+                ),
+                layout[0],
+                palette,
+            );
+        }
+        if let Some(text_select) = self.text_select.as_mut() {
+            frame.render_stateful_widget(
+                TextSelect::new(Theme::sheet_block(), Theme::table_highlight()),
+                layout[0],
+                text_select,
+            );
+        }
+        if let Some(copy_menu) = self.copy_menu.as_mut() {
+            frame.render_stateful_widget(
+                CopyMenu::new(Theme::sheet_block(), Theme::table_highlight()),
+                layout[0],
+                copy_menu,
+            );
+        }
+        if let Some(message) = self.message.as_mut() {
+            frame.render_stateful_widget(
+                MessagePopup::new(Theme::sheet_block()),
+                layout[0],
+                message,
+            );
+        }
+        if let Some(completion) = self.completion.as_ref().filter(|completion| {
+            matches!(state, AppState::Command)
+                && self.status_bar.prompt_text().as_ref() == Some(&completion.prompt)
+        }) {
+            let title = format!("{} ({})", completion.value.column, completion.count);
+            frame.render_widget(
+                Completions::new(&title, &completion.values, Theme::sheet_block()),
+                layout[0],
+            );
         }
         if let Some(tab) = self.tabs.selected() {
+            let hidden = tab.hidden_columns().to_string();
+            let bookmarks = tab.bookmarks().to_string();
+            let loaded = tab.loaded_at().format("%H:%M:%S").to_string();
+            let path = match tab.tabular_type() {
+                TabularType::Name(name) if self.show_path => self
+                    .sql
+                    .path(name)
+                    .map(|path| path.to_string_lossy().into_owned()),
+                _ => None,
+            };
             self.status_bar.render::<Theme>(
                 frame,
                 layout[1],
@@ -147,14 +534,18 @@ impl App {
                         match tab.tabular_type() {
                             TabularType::Help => "Table",
                             TabularType::Schema => "Table",
+                            TabularType::Errors => "Table",
+                            TabularType::Metadata(_) => "Metadata",
                             TabularType::Name(_) => "Table",
                             TabularType::Query(_) => "SQL",
+                            TabularType::Aggregation(_, _) => "Group",
+                            TabularType::Pivot(_, _) => "Pivot",
+                            TabularType::Plan(_) => "Plan",
+                            TabularType::Search(_) => "Search",
                         },
                         match tab.tabular_type() {
-                            TabularType::Help => "Help",
-                            TabularType::Schema => "Schema",
-                            TabularType::Name(name) => name,
-                            TabularType::Query(query) => query,
+                            TabularType::Name(name) => path.as_deref().unwrap_or(name),
+                            tabular_type => tabular_type.description(),
                         },
                     ),
                     (
@@ -182,39 +573,328 @@ impl App {
                             tab.table_values().width()
                         ),
                     ),
-                ],
+                ]
+                .into_iter()
+                .chain((tab.hidden_columns() > 0).then_some(("Hidden", hidden.as_str())))
+                .chain((tab.bookmarks() > 0).then_some(("Marks", bookmarks.as_str())))
+                .chain(tab.is_preview().then_some(("Preview", ":full")))
+                .chain(
+                    self.recording
+                        .as_ref()
+                        .map(|recording| ("Recording", recording.name.as_str())),
+                )
+                .chain(tab.read_options().is_some().then_some((
+                    if self.watch { "Watching" } else { "Loaded" },
+                    loaded.as_str(),
+                )))
+                .collect::<Vec<_>>(),
             )
         } else {
             self.status_bar.render::<Theme>(frame, layout[1], &[])
         }
     }
 
+    /// Looks up the values of the column compared at the end of the filter
+    /// prompt that start with what has been typed so far. The distinct values
+    /// of the column are computed once and kept until the prompt is closed.
+    fn update_completion(&mut self) -> AppResult<()> {
+        self.completion = self.status_bar.prompt_text().and_then(|prompt| {
+            let expr = prompt
+                .strip_prefix(":F ")
+                .or_else(|| prompt.strip_prefix(":filter "))?;
+            let value = filter_value_at_end(expr)?;
+            let series = self
+                .tabs
+                .selected()?
+                .data_frame()
+                .column(&value.column)
+                .ok()?;
+            if self
+                .distinct
+                .as_ref()
+                .is_none_or(|(column, _)| column != &value.column)
+            {
+                self.distinct = Some((value.column.clone(), distinct_values(series).ok()?));
+            }
+            let (_, distinct) = self.distinct.as_ref()?;
+            let matches = &distinct[prefix_range(distinct, &value.partial)];
+            let (first, last) = (matches.first()?, matches.last()?);
+            let common = first
+                .chars()
+                .zip(last.chars())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a)
+                .collect();
+            Some(Completion {
+                is_str: series.dtype() == &DataType::String,
+                prompt,
+                value,
+                values: matches.iter().take(COMPLETION_ROWS).cloned().collect(),
+                count: matches.len(),
+                common,
+            })
+        });
+        Ok(())
+    }
+
+    /// Completes the filter prompt with the common prefix of the values, and
+    /// closes the quote if only one value is left.
+    fn complete(&mut self) -> AppResult<()> {
+        let Some(completion) = self
+            .completion
+            .take()
+            .filter(|completion| self.status_bar.prompt_text() == Some(completion.prompt.clone()))
+        else {
+            return Ok(());
+        };
+        let common = completion.common.chars().collect::<Vec<_>>();
+        let partial = completion.value.partial.chars().count();
+        let unique = completion.count == 1;
+        if !unique && common.len() <= partial {
+            self.completion = Some(completion);
+            return Ok(());
+        }
+        let quote = completion
+            .value
+            .quote
+            .or(completion.is_str.then_some('\''));
+        let text = completion
+            .value
+            .quote
+            .is_none()
+            .then_some(quote)
+            .flatten()
+            .into_iter()
+            .chain(common)
+            .chain(unique.then_some(quote).flatten())
+            .collect::<String>();
+        self.status_bar.complete(partial, &text)?;
+        self.update_completion()
+    }
+
     pub fn handle_key_event(&mut self, key_event: KeyEvent) -> AppResult<()> {
+        if !self.replaying {
+            if let Some(recording) = self.recording.as_mut() {
+                recording.pending.push(key_event);
+            }
+        }
+        self.handle_key(key_event)?;
+        let state = self.infer_state();
+        // Keys of interactions ending in an error are left out of macros
+        if let Some(recording) = self.recording.as_mut() {
+            match state {
+                AppState::Empty | AppState::Table | AppState::Sheet | AppState::Running => {
+                    recording.keys.append(&mut recording.pending)
+                }
+                AppState::Error => recording.pending.clear(),
+                _ => (),
+            }
+        }
+        Ok(())
+    }
+
+    /// Handles the keys of a macro in order, waiting for the queries they run
+    /// and stopping at the first error.
+    fn replay(&mut self, keys: &[KeyEvent]) -> AppResult<()> {
+        for (idx, key_event) in keys.iter().enumerate() {
+            self.handle_key_event(*key_event)?;
+            self.fetch_query(true)?;
+            if let StatusBarState::Error(error) = self.status_bar.state() {
+                Err(format!(
+                    "Macro stopped at key {} of {}: {}",
+                    idx + 1,
+                    keys.len(),
+                    error
+                ))?
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key_event: KeyEvent) -> AppResult<()> {
+        self.status_bar.clear_message()?;
         let state = self.infer_state();
+        if state != AppState::Command {
+            self.distinct = None;
+        }
         let key_code = key_event.code;
         match (state, key_code) {
             (AppState::Command | AppState::Error, KeyCode::Esc) => self.status_bar.show_info(),
 
             (AppState::Command, KeyCode::Enter) => {
                 if let Some(command) = self.status_bar.commit_prompt() {
-                    let (s1, s2) = command.split_once(' ').unwrap_or((command.as_ref(), ""));
-                    if let Some(parse_fn) = self.exec_table.get(s1) {
-                        match parse_fn(s2).and_then(|action| self.invoke(action)) {
-                            Ok(_) => self.status_bar.show_info(),
-                            Err(error) => self.status_bar.show_error(error),
-                        }
-                    } else {
-                        self.status_bar.show_error("Command not found")
-                    }
+                    self.run_command(&command)
                 } else {
                     self.status_bar
                         .show_error("Invalid state; consider restarting Tabiew")
                 }
             }
 
-            (AppState::Command, _) => self.status_bar.input(key_event),
+            (AppState::Command, KeyCode::Char('v'))
+                if key_event.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                match self.clipboard.get_text() {
+                    Ok(text) => self
+                        .status_bar
+                        .paste(&text)
+                        .and_then(|_| self.update_completion()),
+                    Err(error) => self.status_bar.show_error(error),
+                }
+            }
+
+            (AppState::Command, KeyCode::Tab) => self.complete(),
+
+            (AppState::Command, _) => self
+                .status_bar
+                .input(key_event)
+                .and_then(|_| self.update_completion()),
+
+            (AppState::Palette, KeyCode::Esc) => {
+                self.palette = None;
+                Ok(())
+            }
+
+            (AppState::Palette, KeyCode::Enter) => {
+                match self.palette.take().and_then(|palette| palette.selected().cloned()) {
+                    Some(item) if item.usage == item.name => self.run_command(item.name),
+                    Some(item) => self
+                        .status_bar
+                        .show_prompt(format!("{} ", item.name.trim_start_matches(':'))),
+                    None => Ok(()),
+                }
+            }
+
+            (AppState::Palette, _) => {
+                if let Some(palette) = self.palette.as_mut() {
+                    match (key_event.code, key_event.modifiers) {
+                        (KeyCode::Up, _) | (KeyCode::Char('p'), KeyModifiers::CONTROL) => {
+                            palette.select_up();
+                        }
+                        (KeyCode::Down, _) | (KeyCode::Char('n'), KeyModifiers::CONTROL) => {
+                            palette.select_down();
+                        }
+                        (KeyCode::Backspace, _) => {
+                            palette.delete_backward();
+                        }
+                        (KeyCode::Char(c), KeyModifiers::NONE | KeyModifiers::SHIFT) => {
+                            palette.input_char(c);
+                        }
+                        _ => (),
+                    }
+                }
+                Ok(())
+            }
+
+            (AppState::Message, _) => {
+                if let Some(message) = self.message.as_mut() {
+                    match key_code {
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            message.scroll_up();
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            message.scroll_down();
+                        }
+                        _ => self.message = None,
+                    }
+                }
+                Ok(())
+            }
+
+            (AppState::TextSelect, KeyCode::Esc | KeyCode::Char('q')) => {
+                self.text_select = None;
+                Ok(())
+            }
+
+            (AppState::TextSelect, KeyCode::Enter | KeyCode::Char('y')) => {
+                if let Some(text) = self.text_select.take().map(|state| state.selected_text()) {
+                    let len = text.chars().count();
+                    match self.clipboard.set_text(text) {
+                        Ok(()) => self
+                            .status_bar
+                            .show_message(format!("Copied {} character(s)", len)),
+                        Err(error) => self.status_bar.show_error(error),
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+
+            (AppState::TextSelect, _) => {
+                if let Some(text_select) = self.text_select.as_mut() {
+                    match key_code {
+                        KeyCode::Left | KeyCode::Char('h') => text_select.move_left(),
+                        KeyCode::Right | KeyCode::Char('l') => text_select.move_right(),
+                        KeyCode::Char('b') => text_select.move_word_left(),
+                        KeyCode::Char('w') | KeyCode::Char('e') => text_select.move_word_right(),
+                        KeyCode::Home | KeyCode::Char('0') => text_select.move_home(),
+                        KeyCode::End | KeyCode::Char('$') => text_select.move_end(),
+                        KeyCode::Char('v') => text_select.toggle_anchor(),
+                        _ => text_select,
+                    };
+                }
+                Ok(())
+            }
+
+            (AppState::CopyMenu, KeyCode::Esc | KeyCode::Char('q')) => {
+                self.copy_menu = None;
+                Ok(())
+            }
+
+            (AppState::CopyMenu, KeyCode::Enter | KeyCode::Char('y')) => {
+                if let Some((format, text)) = self
+                    .copy_menu
+                    .take()
+                    .and_then(|state| state.selected().cloned())
+                {
+                    match self.clipboard.set_text(text) {
+                        Ok(()) => self
+                            .status_bar
+                            .show_message(format!("Copied {} value", format)),
+                        Err(error) => self.status_bar.show_error(error),
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+
+            (AppState::CopyMenu, _) => {
+                if let Some(copy_menu) = self.copy_menu.as_mut() {
+                    match key_code {
+                        KeyCode::Up | KeyCode::Char('k') => copy_menu.select_up(),
+                        KeyCode::Down | KeyCode::Char('j') => copy_menu.select_down(),
+                        _ => copy_menu,
+                    };
+                }
+                Ok(())
+            }
+
+            (AppState::Confirm, KeyCode::Char('y') | KeyCode::Char('Y')) => {
+                self.status_bar.show_info()?;
+                match self.pending.take().map(|action| self.perform(action)) {
+                    Some(Err(error)) => self.status_bar.show_error(error),
+                    _ => Ok(()),
+                }
+            }
+
+            (AppState::Confirm, _) => {
+                self.pending = None;
+                self.status_bar.show_info()
+            }
+
+            (AppState::Running, KeyCode::Esc) => {
+                if let Some(query) = self.query.take() {
+                    query.handle.cancel();
+                    self.cancelled.push(query.handle);
+                }
+                self.status_bar.show_message("Query cancelled")
+            }
+
+            (AppState::Running, _) => Ok(()),
 
-            (_, KeyCode::Char(':')) => self.status_bar.show_prompt(""),
+            (_, KeyCode::Char(':')) => {
+                self.keybindings.clear_pending();
+                self.status_bar.show_prompt("")
+            }
 
             _ => {
                 match self
@@ -229,13 +909,188 @@ impl App {
             }
         }
     }
+    fn run_command(&mut self, command: &str) -> AppResult<()> {
+        let (s1, s2) = command.split_once(' ').unwrap_or((command, ""));
+        if let Some(parse_fn) = self.exec_table.get(s1) {
+            let mut query = false;
+            match parse_fn(s2).and_then(|action| {
+                query = matches!(
+                    action,
+                    AppAction::SqlQuery(_) | AppAction::SqlExplain(_) | AppAction::TabNew(_)
+                );
+                self.status_bar.show_info()?;
+                self.invoke(action)
+            }) {
+                Ok(_) => Ok(()),
+                Err(error) if query => {
+                    self.message = Some(MessagePopupState::new("Query failed", error.to_string()));
+                    self.status_bar.show_error(error)
+                }
+                Err(error) => self.status_bar.show_error(error),
+            }
+        } else {
+            self.status_bar.show_error("Command not found")
+        }
+    }
+
+    /// Appends the column to the data frame of the selected tab, keeping the
+    /// selected row. For a file's tab, the data frame is registered as a table
+    /// of its own, e.g. `sales_2`, leaving the file's table as read; its name
+    /// is returned.
+    fn add_column(&mut self, series: Series) -> AppResult<Option<String>> {
+        let Some(tab) = self.tabs.selected_mut() else {
+            return Ok(None);
+        };
+        let name = series.name().to_owned();
+        if tab.data_frame().column(&name).is_ok() {
+            Err(format!("Column '{}' already exists", name))?
+        }
+        let df = tab.data_frame().hstack(&[series])?;
+        let selected = tab.selected();
+        tab.set_data_frame(df.clone())?;
+        tab.select(selected)?;
+        let TabularType::Name(table) = tab.tabular_type() else {
+            return Ok(None);
+        };
+        let table = if self.derived.contains(table) {
+            self.sql.update(table, df)?;
+            table.to_owned()
+        } else {
+            let path = self.sql.path(table).cloned().unwrap_or_default();
+            let table = self.sql.register(table, df, path);
+            self.derived.insert(table.clone());
+            tab.set_tabular_type(TabularType::Name(table.clone()));
+            table
+        };
+        Ok(Some(table))
+    }
+
+    /// Loaded tables with the number of the tab showing each, if any, to
+    /// jump to it with `Alt+N` or `:tab`.
+    fn schema(&self) -> AppResult<DataFrame> {
+        let mut df = self.sql.schema();
+        let tabs = df
+            .column("Table")?
+            .str()?
+            .into_iter()
+            .map(|table| {
+                self.tabs
+                    .iter()
+                    .position(|tab| match tab.tabular_type() {
+                        TabularType::Name(name) => Some(name.as_str()) == table,
+                        _ => false,
+                    })
+                    .map(|idx| idx as u32 + 1)
+            })
+            .collect::<Vec<_>>();
+        df.insert_column(0, Series::new("Tab", tabs))?;
+        Ok(df)
+    }
+
+    /// Cells of every tab but the search results containing the text,
+    /// ignoring case, one per row with the tab, column, and row numbers.
+    fn search_tabs(&self, text: &str) -> AppResult<DataFrame> {
+        let text = text.to_lowercase();
+        let (mut tabs, mut tables, mut columns, mut rows, mut values) =
+            (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        'tabs: for (idx, tab) in self.tabs.iter().enumerate() {
+            if matches!(tab.tabular_type(), TabularType::Search(_)) {
+                continue;
+            }
+            for series in tab.data_frame().get_columns() {
+                for (row, value) in series.iter().enumerate() {
+                    if value.is_null() {
+                        continue;
+                    }
+                    let value = any_value_into_string(value);
+                    if !value.to_lowercase().contains(&text) {
+                        continue;
+                    }
+                    tabs.push(idx as u32 + 1);
+                    tables.push(tab.tabular_type().description().to_owned());
+                    columns.push(series.name().to_owned());
+                    rows.push(row as u32 + 1);
+                    values.push(value);
+                    if values.len() == SEARCH_MAX_MATCHES {
+                        break 'tabs;
+                    }
+                }
+            }
+        }
+        Ok(DataFrame::new(vec![
+            Series::new("Tab", tabs),
+            Series::new("Table", tables),
+            Series::new("Column", columns),
+            Series::new("Row", rows),
+            Series::new("Value", values),
+        ])?)
+    }
+
+    /// Data frame of the open tab of the table, or of the SQL table if no tab
+    /// shows it.
+    fn table_data_frame(&mut self, name: &str) -> Result<DataFrame, String> {
+        let tab = self.tabs.iter().find(
+            |tab| matches!(tab.tabular_type(), TabularType::Name(tab_name) if tab_name == name),
+        );
+        match tab {
+            Some(tab) => Ok(tab.data_frame().clone()),
+            None if self.sql.contains_dataframe(name) => self
+                .sql
+                .execute(&format!("SELECT * FROM {}", name))
+                .map_err(|err| err.to_string()),
+            None => Err(format!("Table '{}' not found", name)),
+        }
+    }
+
+    fn show_match(&mut self, (position, count): (usize, usize)) -> AppResult<()> {
+        self.status_bar
+            .show_message(format!("Match {} of {}", position, count))
+    }
+
+    /// Question asked before performing the action, if it is destructive and
+    /// confirmations are enabled.
+    fn confirmation(&self, action: &AppAction) -> Option<String> {
+        if !self.confirm {
+            return None;
+        }
+        match action {
+            AppAction::Quit => Some("Quit Tabiew?".to_owned()),
+            AppAction::TabRemoveOrQuit if self.tabs.len() == 1 => Some("Quit Tabiew?".to_owned()),
+            AppAction::TabRemoveOrQuit | AppAction::TabRemoveSelected if !self.tabs.is_empty() => {
+                Some(format!("Close tab {}?", self.tabs.idx() + 1))
+            }
+            AppAction::TabRemove(idx) if *idx < self.tabs.len() => {
+                Some(format!("Close tab {}?", idx + 1))
+            }
+            AppAction::TabularExport(path, _, _) if path.exists() && !self.tabs.is_empty() => {
+                Some(format!("Overwrite {}?", path.display()))
+            }
+            _ => None,
+        }
+    }
+
     fn invoke(&mut self, action: Action) -> AppResult<()> {
+        if let Some(question) = self.confirmation(&action) {
+            self.pending = Some(action);
+            self.status_bar.show_confirm(question)
+        } else {
+            self.perform(action)
+        }
+    }
+
+    fn perform(&mut self, action: Action) -> AppResult<()> {
         match action {
             AppAction::StatusBarStats => self.status_bar.show_info(),
 
             AppAction::StatusBarCommand(prefix) => self.status_bar.show_prompt(prefix),
 
+            AppAction::CommandRun(command) => self.run_command(&command),
+
             AppAction::StatausBarError(msg) => self.status_bar.show_error(msg),
+            AppAction::StatusBarTogglePath => {
+                self.show_path = !self.show_path;
+                Ok(())
+            }
 
             AppAction::TabularTableView => {
                 if let Some(tab) = self.tabs.selected_mut() {
@@ -262,26 +1117,92 @@ impl App {
             }
 
             AppAction::SqlQuery(query) => {
-                if let Some(tab) = self.tabs.selected_mut() {
-                    tab.set_data_frame(self.sql.execute(&query)?)
+                if self.tabs.selected().is_some() {
+                    let query = self.sql.substitute(&query)?;
+                    self.start_query(&query, None)
                 } else {
                     Ok(())
                 }
             }
 
+            AppAction::SqlExplain(query) => {
+                let df = self.sql.explain(&self.sql.substitute(&query)?)?;
+                self.tabs.add(Tabular::new(df, TabularType::Plan(query)))?;
+                self.tabs.select_last()
+            }
+
+            AppAction::SqlSetVariable(name, literal) => {
+                self.sql.set_variable(&name, &literal);
+                self.status_bar
+                    .show_message(format!("Set ${} = {}", name, literal))
+            }
+
+            AppAction::SqlUnsetVariable(name) => {
+                if !self.sql.unset_variable(&name) {
+                    Err(format!("Variable '${}' is not set", name))?
+                }
+                self.status_bar.show_message(format!("Unset ${}", name))
+            }
+
+            AppAction::SqlVariables => {
+                let variables = self
+                    .sql
+                    .variables()
+                    .map(|(name, literal)| format!("${} = {}", name, literal))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.status_bar.show_message(if variables.is_empty() {
+                    "No variables are set, use :set <name> = <value>".to_owned()
+                } else {
+                    variables
+                })
+            }
+
             AppAction::SqlSchema => {
                 let idx = self.tabs.iter().enumerate().find_map(|(idx, tab)| {
                     matches!(tab.tabular_type(), TabularType::Schema).then_some(idx)
                 });
+                let df = self.schema()?;
                 if let Some(idx) = idx {
-                    self.tabs.select(idx)
-                } else {
+                    self.tabs.select(idx)?;
                     self.tabs
-                        .add(Tabular::new(self.sql.schema(), TabularType::Schema))?;
+                        .selected_mut()
+                        .map_or(Ok(()), |tab| tab.set_data_frame(df))
+                } else {
+                    self.tabs.add(Tabular::new(df, TabularType::Schema))?;
+                    self.tabs.select_last()
+                }
+            }
+
+            AppAction::ErrorLog => {
+                let idx = self.tabs.iter().enumerate().find_map(|(idx, tab)| {
+                    matches!(tab.tabular_type(), TabularType::Errors).then_some(idx)
+                });
+                let df = self.status_bar.error_log();
+                if let Some(idx) = idx {
+                    self.tabs.select(idx)?;
+                    match self.tabs.selected_mut() {
+                        Some(tab) => tab.set_data_frame(df),
+                        None => Ok(()),
+                    }
+                } else {
+                    self.tabs.add(Tabular::new(df, TabularType::Errors))?;
                     self.tabs.select_last()
                 }
             }
 
+            AppAction::SqlMetadata => {
+                let name = match self.tabs.selected().map(Tabular::tabular_type) {
+                    Some(TabularType::Name(name)) => name.to_owned(),
+                    _ => Err("Metadata is only available for tabs of loaded files")?,
+                };
+                let path = self.sql.path(&name).ok_or("Table not found")?.clone();
+                let df = read_parquet_metadata(path)
+                    .map_err(|err| format!("Unable to read Parquet metadata: {}", err))?;
+                self.tabs.add(Tabular::new(df, TabularType::Metadata(name)))?;
+                self.tabs.select_last()
+            }
+
             AppAction::TabularGoto(line) => {
                 if let Some(tab) = self.tabs.selected_mut() {
                     tab.select(line)
@@ -362,106 +1283,825 @@ impl App {
                 }
             }
 
-            AppAction::SheetScrollUp => {
+            AppAction::TabularGoLeft(cols) => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    tab.scroll_up()
+                    tab.select_left(cols)
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::SheetScrollDown => {
+            AppAction::TabularGoRight(cols) => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    tab.scroll_down()
+                    tab.select_right(cols)
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::TabularReset => {
+            AppAction::TabularToggleColumn => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    tab.set_data_frame(match tab.tabular_type() {
-                        TabularType::Help => Commands::default().into_data_frame(),
-                        TabularType::Schema => self.sql.schema(),
-                        TabularType::Name(name) => self
-                            .sql
-                            .execute(format!("SELECT * FROM {}", name).as_str())?,
-                        TabularType::Query(query) => self.sql.execute(query)?,
-                    })
+                    tab.toggle_column()
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::TabularSelect(select) => {
+            AppAction::TabularToggleBar(column) => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    let mut sql = SqlBackend::new();
-                    sql.register("df", tab.data_frame().clone(), "".into());
-                    tab.set_data_frame(sql.execute(&format!("SELECT {} FROM df", select))?)
+                    let column = match column.as_str() {
+                        "" => tab
+                            .selected_column_name()
+                            .ok_or("No column to draw bars for")?,
+                        column => column,
+                    }
+                    .to_owned();
+                    tab.toggle_bar(&column)
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::TabularOrder(order) => {
+            AppAction::TabularDateFormat(format, column) => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    let mut sql = SqlBackend::new();
-                    sql.register("df", tab.data_frame().clone(), "".into());
-                    tab.set_data_frame(
-                        sql.execute(&format!("SELECT * FROM df ORDER BY {}", order))?,
-                    )
+                    let target = match &column {
+                        Some(column) => format!("'{}'", column),
+                        None => "all columns".to_owned(),
+                    };
+                    let message = match &format {
+                        Some(format) => format!("Dates of {} shown as {}", target, format),
+                        None => format!("Dates of {} shown in the default format", target),
+                    };
+                    match column {
+                        Some(column) => tab.set_column_date_format(&column, format)?,
+                        None => tab.set_date_format(format),
+                    }
+                    self.status_bar.show_message(message)
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::TabularFilter(filter) => {
+            AppAction::TabularColumnWidth(column, percent) => {
                 if let Some(tab) = self.tabs.selected_mut() {
-                    let mut sql = SqlBackend::new();
-                    sql.register("df", tab.data_frame().clone(), "".into());
-                    tab.set_data_frame(sql.execute(&format!("SELECT * FROM df where {}", filter))?)
+                    let column = match column.as_str() {
+                        "" => tab
+                            .selected_column_name()
+                            .ok_or("No column to set the width of")?,
+                        column => column,
+                    }
+                    .to_owned();
+                    tab.set_column_width(&column, percent)
                 } else {
                     Ok(())
                 }
             }
 
-            AppAction::TabNew(query) => {
-                if self.sql.contains_dataframe(&query) {
-                    let df = self.sql.execute(&format!("SELECT * FROM {}", query))?;
-                    self.tabs.add(Tabular::new(df, TabularType::Name(query)))?;
+            AppAction::TabularHighlight(column, op, value, color) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.add_highlight(&column, &op, &value, color)
                 } else {
-                    let df = self.sql.execute(&query)?;
-                    self.tabs.add(Tabular::new(df, TabularType::Query(query)))?;
+                    Ok(())
                 }
-                self.tabs.select_last()
             }
 
-            AppAction::TabSelect(idx) => {
-                if idx == 0 {
-                    Err("zero is not a valid tab".into())
-                } else if idx <= self.tabs.len() {
-                    self.tabs.select(idx.saturating_sub(1))
+            AppAction::TabularResetHighlights(column) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.reset_highlights(column.as_deref())
                 } else {
-                    Err(format!(
-                        "index {} is out of bound, maximum is {}",
-                        idx,
-                        self.tabs.len()
-                    )
-                    .into())
+                    Ok(())
                 }
             }
 
-            AppAction::TabRemove(idx) => self.tabs.remove(idx),
-
-            AppAction::TabRemoveSelected => self.tabs.remove(self.tabs.idx()),
-
-            AppAction::TabRename(_idx, _new_name) => {
-                todo!()
+            AppAction::TabularShowAllColumns => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.show_all_columns()
+                } else {
+                    Ok(())
+                }
             }
 
-            AppAction::TabSelectedPrev => self.tabs.select_prev(),
+            AppAction::TabularToggleThousands => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_thousands()
+                } else {
+                    Ok(())
+                }
+            }
 
-            AppAction::TabSelectedNext => self.tabs.select_next(),
+            AppAction::TabularCycleBoolGlyphs => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.cycle_bool_glyphs()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCycleIntBase => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let (column, base) = tab.cycle_int_base()?;
+                    self.status_bar
+                        .show_message(format!("Showing {} in {}", column, base))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCycleHeaderMode => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.cycle_header_mode()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleMissing => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_missing()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleFooter => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_footer()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleTotals => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_totals()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleCompare => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_compare()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleGutter => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_gutter()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleDense => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_dense()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCopyColumn(style, force) => {
+                if let Some(series) = self
+                    .tabs
+                    .selected()
+                    .and_then(|tab| tab.data_frame().get_columns().get(tab.selected_column()))
+                {
+                    if series.len() > COPY_WARN_ROWS && !force {
+                        Err(format!(
+                            "Column has {} values; use ':copycol {} force' to copy anyway",
+                            series.len(),
+                            style
+                        ))?
+                    }
+                    let (name, len) = (series.name().to_owned(), series.len());
+                    self.clipboard.set_text(series_to_list(series, style))?;
+                    self.status_bar
+                        .show_message(format!("Copied {} value(s) of '{}'", len, name))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCopyColumnName => {
+                if let Some(name) = self.tabs.selected().and_then(Tabular::selected_column_name) {
+                    let name = name.to_owned();
+                    self.clipboard.set_text(name.clone())?;
+                    self.status_bar
+                        .show_message(format!("Copied column name '{}'", name))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCopyView(force) => {
+                if let Some(tab) = self.tabs.selected() {
+                    let df = tab.view_data_frame()?;
+                    if df.height() > COPY_WARN_ROWS && !force {
+                        Err(format!(
+                            "Table has {} rows; use ':copytsv force' to copy anyway",
+                            df.height()
+                        ))?
+                    }
+                    self.clipboard.set_text(data_frame_to_tsv(&df))?;
+                    self.status_bar.show_message(format!(
+                        "Copied {} row(s) and {} column(s) as TSV",
+                        df.height(),
+                        df.width()
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularExport(path, separator, quote_style) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let mut df = tab.data_frame().clone();
+                    let file = fs::File::create(&path)
+                        .map_err(|err| format!("Unable to create {}: {}", path.display(), err))?;
+                    CsvWriter::new(file)
+                        .with_separator(separator)
+                        .with_quote_style(quote_style)
+                        .finish(&mut df)?;
+                    self.status_bar.show_message(format!(
+                        "Exported {} row(s) to {}",
+                        df.height(),
+                        path.display()
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularToggleBookmark => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.toggle_bookmark()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularGotoNextBookmark => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.select_next_bookmark()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularGotoPrevBookmark => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.select_prev_bookmark()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::SheetScrollUp => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.scroll_up()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::SheetScrollDown => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.scroll_down()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularReset => {
+                let listing = match self.tabs.selected().map(Tabular::tabular_type) {
+                    Some(TabularType::Search(text)) => Some(self.search_tabs(text)?),
+                    Some(TabularType::Schema) => Some(self.schema()?),
+                    _ => None,
+                };
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.set_data_frame(match tab.tabular_type() {
+                        TabularType::Help => Commands::default().into_data_frame(),
+                        TabularType::Schema => listing.unwrap_or_default(),
+                        TabularType::Errors => self.status_bar.error_log(),
+                        TabularType::Metadata(name) => read_parquet_metadata(
+                            self.sql.path(name).ok_or("Table not found")?.clone(),
+                        )?,
+                        TabularType::Name(name) => self
+                            .sql
+                            .execute(format!("SELECT * FROM {}", name).as_str())?,
+                        TabularType::Query(query) => {
+                            let query = self.sql.substitute(query)?;
+                            self.sql.execute(&query)?
+                        }
+                        TabularType::Aggregation(_, df) => df.clone(),
+                        TabularType::Pivot(_, df) => df.clone(),
+                        TabularType::Plan(query) => {
+                            let query = self.sql.substitute(query)?;
+                            self.sql.explain(&query)?
+                        }
+                        TabularType::Search(_) => listing.unwrap_or_default(),
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularLoadFull => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    match tab.tabular_type() {
+                        TabularType::Name(name) if tab.is_preview() => {
+                            let path = self.sql.path(name).ok_or("Table not found")?;
+                            let df = tab
+                                .read_options()
+                                .ok_or("Read options not found")?
+                                .read(path)?;
+                            self.sql.update(name, df.clone())?;
+                            tab.set_data_frame(df)?;
+                            tab.mark_loaded();
+                            tab.set_preview(false)
+                        }
+                        _ => Err("Current tab is not a preview".into()),
+                    }
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularSelect(select) => {
+                let select = self.sql.substitute(&select)?;
+                self.start_tab_query(&format!("SELECT {} FROM df", select))
+            }
+
+            AppAction::TabularOrder(order) => {
+                let order = self.sql.substitute(&order)?;
+                self.start_tab_query(&format!("SELECT * FROM df ORDER BY {}", order))
+            }
+
+            AppAction::TabularFilter(filter) => {
+                let filter = self.sql.substitute(&filter)?;
+                self.start_tab_query(&format!("SELECT * FROM df where {}", filter))
+            }
+
+            AppAction::TabularFilterSelectedCell => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.filter_by_selected_cell()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularExplode(column) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let column = match column.as_str() {
+                        "" => tab.selected_column_name().ok_or("No column to explode")?,
+                        column => column,
+                    }
+                    .to_owned();
+                    let df = tab.data_frame();
+                    if !matches!(df.column(&column)?.dtype(), DataType::List(_)) {
+                        Err(format!("Column '{}' is not a list", column))?
+                    }
+                    let df = df.explode([column])?;
+                    tab.set_data_frame(df)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularDropNulls(columns) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let df = tab.data_frame();
+                    if let Some(name) = columns.iter().find(|name| df.column(name).is_err()) {
+                        Err(format!("Column '{}' not found", name))?
+                    }
+                    let subset = (!columns.is_empty()).then_some(columns.as_slice());
+                    let df = df.drop_nulls(subset)?;
+                    let dropped = tab.data_frame().height() - df.height();
+                    tab.set_data_frame(df)?;
+                    self.status_bar
+                        .show_message(format!("Dropped {} row(s) with nulls", dropped))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularDropColumns(columns) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let columns = if columns.is_empty() {
+                        vec![tab
+                            .selected_column_name()
+                            .ok_or("No column to drop")?
+                            .to_owned()]
+                    } else {
+                        columns
+                    };
+                    let df = tab.data_frame();
+                    if let Some(name) = columns.iter().find(|name| df.column(name).is_err()) {
+                        Err(format!("Column '{}' not found", name))?
+                    }
+                    let df = df.drop_many(&columns);
+                    if df.width() == 0 {
+                        Err("Cannot drop every column")?
+                    }
+                    let dropped = tab.data_frame().width() - df.width();
+                    tab.set_data_frame(df)?;
+                    self.status_bar
+                        .show_message(format!("Dropped {} column(s)", dropped))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularSample(rows, seed) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let df = tab.data_frame();
+                    let df = df.sample_n_literal(rows.min(df.height()), false, false, seed)?;
+                    tab.set_data_frame(df)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularReverse => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let df = tab.data_frame().reverse();
+                    let selected = df.height().saturating_sub(tab.selected() + 1);
+                    tab.set_data_frame(df)?;
+                    tab.select(selected)?;
+                    self.status_bar
+                        .show_message("Reversed the row order (use :reset to restore)")
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularRowNumber(name) => {
+                let rows = self
+                    .tabs
+                    .selected()
+                    .map(|tab| tab.data_frame().height() as u32);
+                if let Some(rows) = rows {
+                    let table =
+                        self.add_column(Series::new(&name, (1..=rows).collect::<Vec<_>>()))?;
+                    self.status_bar.show_message(format!(
+                        "Numbered the rows in '{}'{}",
+                        name,
+                        table
+                            .map(|table| format!(", queryable as table {}", table))
+                            .unwrap_or_default()
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularRank(column, descending, name) => {
+                let ranks = self.tabs.selected().map(|tab| {
+                    let series = tab
+                        .data_frame()
+                        .column(&column)
+                        .map_err(|_| format!("Column '{}' not found", column))?;
+                    rank_series(series, &name, descending)
+                        .map_err(|err| format!("Unable to rank '{}': {}", column, err))
+                });
+                if let Some(ranks) = ranks {
+                    let table = self.add_column(ranks?)?;
+                    self.status_bar.show_message(format!(
+                        "Ranked '{}' in '{}'{}",
+                        column,
+                        name,
+                        table
+                            .map(|table| format!(", queryable as table {}", table))
+                            .unwrap_or_default()
+                    ))
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularCastAll(dtype) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let dtype =
+                        parse_dtype(&dtype).ok_or(format!("Invalid data type '{}'", dtype))?;
+                    let (df, failed) = cast_all(tab.data_frame(), &dtype);
+                    let cast = df.width() - failed.len();
+                    tab.set_data_frame(df)?;
+                    self.status_bar.show_message(if failed.is_empty() {
+                        format!("Cast {} column(s) to {}", cast, dtype)
+                    } else {
+                        format!(
+                            "Cast {} column(s) to {}, left unchanged: {}",
+                            cast,
+                            dtype,
+                            failed.join(", ")
+                        )
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularGroup(by, agg, column) => {
+                if let Some(tab) = self.tabs.selected() {
+                    let df = group_aggregate(tab.data_frame(), &by, &agg, column.as_deref())?;
+                    let description = [by.join(","), agg]
+                        .into_iter()
+                        .chain(column)
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    self.tabs.add(Tabular::new(
+                        df.clone(),
+                        TabularType::Aggregation(description, df),
+                    ))?;
+                    self.tabs.select_last()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularPivot(index, columns, values, agg) => {
+                if let Some(tab) = self.tabs.selected() {
+                    let df = pivot_table(tab.data_frame(), &index, &columns, &values, &agg)?;
+                    let description = format!(
+                        "index={} columns={} values={} agg={}",
+                        index, columns, values, agg
+                    );
+                    self.tabs.add(Tabular::new(
+                        df.clone(),
+                        TabularType::Pivot(description, df),
+                    ))?;
+                    self.tabs.select_last()
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularGotoColumn(name) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.goto_column(&name)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularFind(text) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let found = tab.find_in_column(&text)?;
+                    self.show_match(found)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularFindNext => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let found = tab.select_next_match()?;
+                    self.show_match(found)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularFindPrev => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    let found = tab.select_prev_match()?;
+                    self.show_match(found)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabularRenameColumn(old, new) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.rename_column(&old, &new)?;
+                    // Keep the table queried by SQL in sync with the tab, or
+                    // undo the rename
+                    if let TabularType::Name(name) = tab.tabular_type() {
+                        let name = name.to_owned();
+                        let synced = self
+                            .sql
+                            .execute(&format!("SELECT * FROM {}", name))
+                            .and_then(|mut df| {
+                                df.rename(&old, &new)?;
+                                self.sql.update(&name, df)
+                            });
+                        if let Err(err) = synced {
+                            tab.rename_column(&new, &old)?;
+                            Err(format!(
+                                "Unable to rename column '{}' of table '{}': {}",
+                                old, name, err
+                            ))?
+                        }
+                    }
+                    Ok(())
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabNew(query) => {
+                if self.sql.contains_dataframe(&query) {
+                    self.start_query(
+                        &format!("SELECT * FROM {}", query),
+                        Some(TabularType::Name(query)),
+                    )
+                } else {
+                    let substituted = self.sql.substitute(&query)?;
+                    self.start_query(&substituted, Some(TabularType::Query(query)))
+                }
+            }
+
+            AppAction::TabOpen(path, args) => {
+                let path = resolve_path(&path)?;
+                let read_options = self.read_options.with_args(&args)?.for_path(&path);
+                let sheets = read_options
+                    .sheets(&path)
+                    .map_err(|err| format!("Unable to open {}: {}", path.display(), err))?;
+                let count = sheets.len();
+                let first = self.tabs.len();
+                let mut names = Vec::with_capacity(count);
+                let mut converted = Vec::new();
+                for read_options in sheets {
+                    let (df, columns) = read_options
+                        .read_reporting(&path, false)
+                        .map_err(|err| format!("Unable to open {}: {}", path.display(), err))?;
+                    let name = sheet_table_name(&path, read_options.sheet.as_deref(), count)
+                        .ok_or("Invalid file name")?;
+                    let name = self.sql.register(&name, df.clone(), path.clone());
+                    converted.extend(columns);
+                    names.push(name.clone());
+                    self.tabs.add(
+                        Tabular::new(df, TabularType::Name(name)).with_read_options(read_options),
+                    )?;
+                }
+                let message = match names.as_slice() {
+                    [name] => format!("Opened {} as table {}", path.display(), name),
+                    names => format!("Opened {} as tables {}", path.display(), names.join(", ")),
+                };
+                let message = if converted.is_empty() {
+                    message
+                } else {
+                    format!(
+                        "{}, parsing thousands separators in {}",
+                        message,
+                        converted.join(", ")
+                    )
+                };
+                self.tabs.select(first)?;
+                self.status_bar.show_message(message)
+            }
+
+            AppAction::TabSearch(text) => {
+                let df = self.search_tabs(&text)?;
+                if df.height() == 0 {
+                    Err(format!("No tab contains '{}'", text))?
+                }
+                let message = if df.height() == SEARCH_MAX_MATCHES {
+                    format!(
+                        "Showing the first {} matches (Enter to jump to one)",
+                        SEARCH_MAX_MATCHES
+                    )
+                } else {
+                    format!("{} match(es) (Enter to jump to one)", df.height())
+                };
+                self.tabs.add(Tabular::new(df, TabularType::Search(text)))?;
+                self.tabs.select_last()?;
+                self.status_bar.show_message(message)
+            }
+
+            AppAction::TabGotoMatch => {
+                let Some(tab) = self
+                    .tabs
+                    .selected()
+                    .filter(|tab| matches!(tab.tabular_type(), TabularType::Search(_)))
+                else {
+                    return Ok(());
+                };
+                let field = |name: &str| {
+                    tab.data_frame()
+                        .column(name)
+                        .ok()
+                        .and_then(|series| series.get(tab.selected()).ok())
+                        .map(any_value_into_string)
+                        .ok_or(format!("Column '{}' not found; use :reset", name))
+                };
+                let (idx, column, row) = (field("Tab")?, field("Column")?, field("Row")?);
+                let idx = idx.parse::<usize>()? - 1;
+                let row = row.parse::<usize>()? - 1;
+                if self.tabs.iter().nth(idx).is_none_or(|tab| {
+                    matches!(tab.tabular_type(), TabularType::Search(_))
+                        || tab.data_frame().height() <= row
+                }) {
+                    Err(format!(
+                        "Tab {} changed since the search; use :reset",
+                        idx + 1
+                    ))?
+                }
+                self.tabs.select(idx)?;
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.select(row)?;
+                    tab.goto_column(&column)?;
+                }
+                self.status_bar.show_message(format!(
+                    "Tab {}, row {} of '{}'",
+                    idx + 1,
+                    row + 1,
+                    column
+                ))
+            }
+
+            AppAction::TabJoin(left, right, left_key, right_key, how) => {
+                let left_df = self.table_data_frame(&left)?;
+                let right_df = self.table_data_frame(&right)?;
+                let df = join_frames(
+                    (&left, &left_df),
+                    (&right, &right_df),
+                    (&left_key, &right_key),
+                    &how,
+                )?;
+                let name = self.sql.register(
+                    "join",
+                    df.clone(),
+                    format!(
+                        ":join {} {} ON {}={} {}",
+                        left, right, left_key, right_key, how
+                    )
+                    .into(),
+                );
+                self.tabs.add(Tabular::new(df, TabularType::Name(name)))?;
+                self.tabs.select_last()?;
+                self.status_bar.show_message(format!(
+                    "Joined {} and {} on {} = {} ({} join)",
+                    left, right, left_key, right_key, how
+                ))
+            }
+
+            AppAction::TabConcat(names) => {
+                let names = if names.is_empty() {
+                    self.tabs
+                        .iter()
+                        .filter_map(|tab| match tab.tabular_type() {
+                            TabularType::Name(name) => Some(name.to_owned()),
+                            _ => None,
+                        })
+                        .collect()
+                } else {
+                    names
+                };
+                let frames = names
+                    .iter()
+                    .map(|name| self.table_data_frame(name))
+                    .collect::<Result<Vec<_>, _>>()?;
+                let df =
+                    concat_frames(&names.iter().cloned().zip(frames.iter()).collect::<Vec<_>>())?;
+                let name = self.sql.register(
+                    "concat",
+                    df.clone(),
+                    format!(":concat {}", names.join(" ")).into(),
+                );
+                self.tabs.add(Tabular::new(df, TabularType::Name(name)))?;
+                self.tabs.select_last()
+            }
+
+            AppAction::TabSelect(idx) => {
+                if idx == 0 {
+                    Err("zero is not a valid tab".into())
+                } else if idx <= self.tabs.len() {
+                    self.tabs.select(idx.saturating_sub(1))
+                } else {
+                    Err(format!(
+                        "index {} is out of bound, maximum is {}",
+                        idx,
+                        self.tabs.len()
+                    )
+                    .into())
+                }
+            }
+
+            AppAction::TabRemove(idx) => self.tabs.remove(idx),
+
+            AppAction::TabRemoveSelected => self.tabs.remove(self.tabs.idx()),
+
+            AppAction::TabRename(_idx, _new_name) => {
+                todo!()
+            }
+
+            AppAction::TabTheme(theme) => {
+                if let Some(tab) = self.tabs.selected_mut() {
+                    tab.set_theme(theme)
+                } else {
+                    Ok(())
+                }
+            }
+
+            AppAction::TabSelectLast => self.tabs.select_last(),
+
+            AppAction::TabSelectedPrev => self.tabs.select_prev(),
+
+            AppAction::TabSelectedNext => self.tabs.select_next(),
 
             AppAction::TabRemoveOrQuit => {
                 if self.tabs.len() == 1 {
@@ -471,6 +2111,121 @@ impl App {
                 }
             }
 
+            AppAction::TextSelectShow => {
+                if let Some((header, value)) =
+                    self.tabs.selected().and_then(Tabular::selected_cell)
+                {
+                    self.text_select = Some(TextSelectState::new(header, &value));
+                }
+                Ok(())
+            }
+
+            AppAction::CopyMenuShow => {
+                if let Some((name, value)) = self.tabs.selected().and_then(|tab| {
+                    let series = tab.data_frame().get_columns().get(tab.selected_column())?;
+                    Some((series.name().to_owned(), series.get(tab.selected()).ok()?))
+                }) {
+                    self.copy_menu = Some(CopyMenuState::new(
+                        name,
+                        CopyFormat::ALL
+                            .into_iter()
+                            .map(|format| (format, format.format(value.clone())))
+                            .collect(),
+                    ));
+                }
+                Ok(())
+            }
+
+            AppAction::MacroRecord(name) => {
+                if let Some(recording) = &self.recording {
+                    Err(format!(
+                        "Already recording macro '{}'; use :macro stop",
+                        recording.name
+                    ))?
+                }
+                if self.replaying {
+                    Err("Macros can not record other macros")?
+                }
+                let message = format!("Recording macro '{}' (:macro stop to finish)", name);
+                self.recording = Some(Recording {
+                    name,
+                    keys: Vec::new(),
+                    pending: Vec::new(),
+                });
+                self.status_bar.show_message(message)
+            }
+
+            AppAction::MacroStop => {
+                let recording = self.recording.take().ok_or("No macro is being recorded")?;
+                if recording.keys.is_empty() {
+                    Err(format!(
+                        "Macro '{}' is empty, nothing recorded",
+                        recording.name
+                    ))?
+                }
+                let message = format!(
+                    "Recorded macro '{}' of {} key(s)",
+                    recording.name,
+                    recording.keys.len()
+                );
+                self.macros.insert(recording.name.clone(), recording.keys);
+                self.last_macro = Some(recording.name);
+                self.status_bar.show_message(message)
+            }
+
+            AppAction::MacroRun(name) => {
+                if self.replaying {
+                    Err("Macros can not run other macros")?
+                }
+                if self.recording.is_some() {
+                    Err("Macros can not run while one is recorded")?
+                }
+                let keys = self
+                    .macros
+                    .get(&name)
+                    .ok_or_else(|| format!("Macro '{}' not found", name))?
+                    .clone();
+                self.last_macro = Some(name.clone());
+                self.replaying = true;
+                let result = self.replay(&keys);
+                self.replaying = false;
+                result?;
+                if matches!(self.status_bar.state(), StatusBarState::Info) {
+                    self.status_bar
+                        .show_message(format!("Ran macro '{}'", name))?;
+                }
+                Ok(())
+            }
+
+            AppAction::MacroRunLast => {
+                let name = self
+                    .last_macro
+                    .clone()
+                    .ok_or("No macro to run; record one with :macro record <name>")?;
+                self.perform(AppAction::MacroRun(name))
+            }
+
+            AppAction::MacroList => {
+                let macros = self
+                    .macros
+                    .iter()
+                    .map(|(name, keys)| format!("{} ({} keys)", name, keys.len()))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                self.status_bar.show_message(if macros.is_empty() {
+                    "No macros are recorded, use :macro record <name>".to_owned()
+                } else {
+                    macros
+                })
+            }
+
+            AppAction::PaletteShow => {
+                self.palette = Some(CommandPaletteState::new(
+                    Commands::default().into_palette_items(),
+                ));
+                Ok(())
+            }
+
             AppAction::Help => {
                 let idx = self.tabs.iter().enumerate().find_map(|(idx, tab)| {
                     matches!(tab.tabular_type(), TabularType::Help).then_some(idx)
@@ -492,7 +2247,41 @@ impl App {
 }
 
 impl Tabs {
-    pub fn add(&mut self, tabular: Tabular) -> AppResult<()> {
+    /// Sets the scrolloff of the current and future tabs.
+    pub fn with_scrolloff(mut self, scrolloff: usize) -> Self {
+        self.scrolloff = scrolloff;
+        for tabular in self.tabulars.iter_mut() {
+            tabular.set_scrolloff(scrolloff);
+        }
+        self
+    }
+
+    /// Sets the widths of columns, as percentages of the table width, in the
+    /// current and future tabs.
+    pub fn with_column_widths(mut self, widths: Vec<(String, u16)>) -> Self {
+        for tabular in self.tabulars.iter_mut() {
+            tabular.set_column_widths(&widths);
+        }
+        self.column_widths = widths;
+        self
+    }
+
+    /// Sets the strftime format dates and times are shown in, in the current
+    /// and future tabs.
+    pub fn with_date_format(mut self, format: Option<String>) -> Self {
+        for tabular in self.tabulars.iter_mut() {
+            tabular.set_date_format(format.clone());
+        }
+        self.date_format = format;
+        self
+    }
+
+    pub fn add(&mut self, mut tabular: Tabular) -> AppResult<()> {
+        tabular.set_scrolloff(self.scrolloff);
+        tabular.set_column_widths(&self.column_widths);
+        if self.date_format.is_some() {
+            tabular.set_date_format(self.date_format.clone());
+        }
         self.tabulars.push(tabular);
         Ok(())
     }
@@ -571,6 +2360,10 @@ impl Tabs {
     pub fn iter(&self) -> impl Iterator<Item = &Tabular> {
         self.tabulars.iter()
     }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Tabular> {
+        self.tabulars.iter_mut()
+    }
 }
 
 impl FromIterator<Tabular> for Tabs {
@@ -578,6 +2371,101 @@ impl FromIterator<Tabular> for Tabs {
         Self {
             tabulars: iter.into_iter().collect(),
             idx: 0,
+            scrolloff: 0,
+            column_widths: Vec::new(),
+            date_format: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use polars::df;
+
+    use super::*;
+
+    fn app() -> App {
+        let mut sql = SqlBackend::new();
+        let df = df!("score" => [3, 1, 2]).unwrap();
+        let name = sql.register("sales", df.clone(), "sales.csv".into());
+        let tabs = [Tabular::new(df, TabularType::Name(name))]
+            .into_iter()
+            .collect::<Tabs>();
+        App::new(
+            tabs,
+            StatusBar::default(),
+            sql,
+            Commands::default().into_exec(),
+            Keybind::default(),
+        )
+    }
+
+    fn query(app: &mut App, query: &str) -> DataFrame {
+        app.run_command(&format!(":Q {}", query)).unwrap();
+        app.fetch_query(true).unwrap();
+        app.tabs.selected().unwrap().data_frame().clone()
+    }
+
+    #[test]
+    fn test_added_columns_in_sql() {
+        let mut app = app();
+        app.run_command(":rownum").unwrap();
+        app.run_command(":rank score desc").unwrap();
+        assert!(matches!(
+            app.tabs.selected().unwrap().tabular_type(),
+            TabularType::Name(name) if name == "sales_2"
+        ));
+        assert_eq!(
+            query(&mut app, "SELECT row_number, score_rank FROM sales_2"),
+            df!("row_number" => [1u32, 2, 3], "score_rank" => [1u32, 3, 2]).unwrap()
+        );
+        assert_eq!(
+            query(&mut app, "SELECT * FROM sales"),
+            df!("score" => [3, 1, 2]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_rename_column_out_of_sync() {
+        let mut app = app();
+        app.run_command(":rename-col score points").unwrap();
+        assert_eq!(
+            query(&mut app, "SELECT points FROM sales"),
+            df!("points" => [3, 1, 2]).unwrap()
+        );
+
+        app.sql
+            .update("sales", df!("other" => [0]).unwrap())
+            .unwrap();
+        let error = app
+            .invoke(AppAction::TabularRenameColumn(
+                "points".to_owned(),
+                "score".to_owned(),
+            ))
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("Unable to rename column 'points' of table 'sales'"));
+        assert_eq!(
+            app.tabs.selected().unwrap().data_frame(),
+            &df!("points" => [3, 1, 2]).unwrap()
+        );
+        assert!(app.sql.update("missing", DataFrame::empty()).is_err());
+    }
+
+    #[test]
+    fn test_variables_in_typed_queries() {
+        let mut app = app();
+        app.run_command(":set min = 2").unwrap();
+        app.run_command(":F score >= $min").unwrap();
+        app.fetch_query(true).unwrap();
+        assert_eq!(
+            app.tabs.selected().unwrap().data_frame(),
+            &df!("score" => [3, 2]).unwrap()
+        );
+        assert_eq!(
+            query(&mut app, "SELECT '$min' AS s, $min AS n FROM sales LIMIT 1"),
+            df!("s" => ["$min"], "n" => [2]).unwrap()
+        );
+    }
+}
@@ -1,4 +1,8 @@
+use std::time::Instant;
+
+use chrono::{DateTime, Local};
 use crossterm::event::{KeyCode, KeyEvent};
+use polars::{frame::DataFrame, prelude::NamedFrom, series::Series};
 use ratatui::{
     layout::{Alignment, Rect},
     style::Style,
@@ -13,17 +17,27 @@ use crate::{
 
 use super::AppResult;
 
+/// Number of errors kept in the error log.
+const ERROR_LOG_SIZE: usize = 100;
+
+/// Frames of the spinner shown while a query runs.
+const SPINNER: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
 #[derive(Debug, Default)]
 pub struct StatusBar {
     state: StatusBarState,
     prompt_history: Vec<String>,
+    errors: Vec<(DateTime<Local>, String)>,
 }
 
 #[derive(Debug, Default)]
 pub enum StatusBarState {
     #[default]
     Info,
+    Message(String),
     Error(String),
+    Confirm(String),
+    Running(String, Instant),
     Prompt(PromptState),
 }
 
@@ -37,8 +51,52 @@ impl StatusBar {
         Ok(())
     }
 
+    pub fn show_message(&mut self, msg: impl ToString) -> AppResult<()> {
+        self.state = StatusBarState::Message(msg.to_string());
+        Ok(())
+    }
+
+    pub fn clear_message(&mut self) -> AppResult<()> {
+        if matches!(self.state, StatusBarState::Message(_)) {
+            self.state = StatusBarState::Info;
+        }
+        Ok(())
+    }
+
     pub fn show_error(&mut self, msg: impl ToString) -> AppResult<()> {
-        self.state = StatusBarState::Error(msg.to_string());
+        let msg = msg.to_string();
+        if self.errors.len() == ERROR_LOG_SIZE {
+            self.errors.remove(0);
+        }
+        self.errors.push((Local::now(), msg.clone()));
+        self.state = StatusBarState::Error(msg);
+        Ok(())
+    }
+
+    /// Errors shown so far, the most recent first.
+    pub fn error_log(&self) -> DataFrame {
+        let (times, errors): (Vec<_>, Vec<_>) = self
+            .errors
+            .iter()
+            .rev()
+            .map(|(time, error)| (time.format("%H:%M:%S").to_string(), error.to_owned()))
+            .unzip();
+        DataFrame::new(vec![
+            Series::new("Time", times),
+            Series::new("Error", errors),
+        ])
+        .expect("Invalid error log")
+    }
+
+    /// Asks the question, to be answered with y or n.
+    pub fn show_confirm(&mut self, question: impl ToString) -> AppResult<()> {
+        self.state = StatusBarState::Confirm(question.to_string());
+        Ok(())
+    }
+
+    /// Shows the message next to a spinner, until another state is shown.
+    pub fn show_running(&mut self, msg: impl ToString) -> AppResult<()> {
+        self.state = StatusBarState::Running(msg.to_string(), Instant::now());
         Ok(())
     }
 
@@ -59,6 +117,34 @@ impl StatusBar {
         }
     }
 
+    /// Text of the prompt, if it is shown and the cursor is at its end.
+    pub fn prompt_text(&self) -> Option<String> {
+        match &self.state {
+            StatusBarState::Prompt(prompt) if prompt.cursor().1 == prompt.command_len() => {
+                Some(prompt.command())
+            }
+            _ => None,
+        }
+    }
+
+    /// Replaces the last `remove` characters of the prompt with the text.
+    pub fn complete(&mut self, remove: usize, text: &str) -> AppResult<()> {
+        if let StatusBarState::Prompt(prompt) = &mut self.state {
+            for _ in 0..remove {
+                prompt.delete_backward();
+            }
+            prompt.input_str(text);
+        }
+        Ok(())
+    }
+
+    pub fn paste(&mut self, text: &str) -> AppResult<()> {
+        if let StatusBarState::Prompt(prompt) = &mut self.state {
+            prompt.input_str(text);
+        }
+        Ok(())
+    }
+
     pub fn tick(&mut self) -> AppResult<()> {
         Ok(())
     }
@@ -72,10 +158,8 @@ impl StatusBar {
                 KeyCode::Down => {
                     prompt.move_down().move_eol();
                 }
-                KeyCode::Left => {
-                    if prompt.cursor().1 > 1 {
-                        prompt.move_left();
-                    }
+                KeyCode::Left if prompt.cursor().1 > 1 => {
+                    prompt.move_left();
                 }
                 KeyCode::Right => {
                     prompt.move_right();
@@ -132,6 +216,13 @@ impl StatusBar {
                 layout,
             ),
 
+            StatusBarState::Message(msg) => frame.render_widget(
+                Line::raw(msg.as_str())
+                    .alignment(Alignment::Center)
+                    .style(Theme::status_bar_info()),
+                layout,
+            ),
+
             StatusBarState::Error(msg) => frame.render_widget(
                 Line::raw(msg.as_str())
                     .alignment(Alignment::Center)
@@ -139,6 +230,26 @@ impl StatusBar {
                 layout,
             ),
 
+            StatusBarState::Confirm(question) => frame.render_widget(
+                Line::raw(format!("{} (y/n)", question))
+                    .alignment(Alignment::Center)
+                    .style(Theme::status_bar_prompt()),
+                layout,
+            ),
+
+            StatusBarState::Running(msg, started) => {
+                let frame_idx = started.elapsed().as_millis() / 100 % SPINNER.len() as u128;
+                frame.render_widget(
+                    Line::raw(format!(
+                        "{} {} (Esc to cancel)",
+                        SPINNER[frame_idx as usize], msg
+                    ))
+                    .alignment(Alignment::Center)
+                    .style(Theme::status_bar_info()),
+                    layout,
+                )
+            }
+
             StatusBarState::Prompt(text) => {
                 frame.render_stateful_widget(
                     Prompt::new(
@@ -1,20 +1,66 @@
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
 use itertools::{izip, Itertools};
-use polars::frame::DataFrame;
+use polars::{
+    datatypes::DataType,
+    frame::DataFrame,
+    prelude::{IdxCa, IdxSize},
+};
 use rand::Rng;
 use ratatui::{
-    layout::{Alignment, Constraint, Margin, Rect},
+    layout::{Alignment, Constraint, Layout, Margin, Rect},
+    style::{Color, Style, Stylize},
     text::{Line, Span},
     widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
     Frame,
 };
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
+    args::AppTheme,
+    reader::ReadOptions,
     theme::Styler,
-    utils::{data_frame_widths, line_count, Scroll, TableValues},
+    utils::{
+        any_value_into_pretty_string, any_value_into_string, bar_fractions, bar_text,
+        column_totals, compare_mask, filter_equal, fit_text, line_count, match_column,
+        missing_annotation, series_summary, BoolGlyphs, IntBase, Scroll, TableValues, ValueFormat,
+    },
 };
 
 use super::AppResult;
 
+/// Minimum width given to a header that is truncated or wrapped.
+const HEADER_MIN_WIDTH: usize = 8;
+
+/// Width of the bars drawn beside the values of columns in bar mode.
+const BAR_WIDTH: usize = 8;
+
+/// How headers longer than the values of their column are shown.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderMode {
+    /// Widen the column to fit the whole header.
+    #[default]
+    Full,
+    /// Cut the header to the width of the values.
+    Truncate,
+    /// Wrap the header onto two lines, cutting what does not fit.
+    Wrap,
+}
+
+impl HeaderMode {
+    pub fn next(self) -> Self {
+        match self {
+            HeaderMode::Full => HeaderMode::Truncate,
+            HeaderMode::Truncate => HeaderMode::Wrap,
+            HeaderMode::Wrap => HeaderMode::Full,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum TabularState {
     Table,
@@ -25,43 +71,141 @@ pub enum TabularState {
 pub enum TabularType {
     Help,
     Schema,
+    Errors,
+    Metadata(String),
     Name(String),
     Query(String),
+    Aggregation(String, DataFrame),
+    Pivot(String, DataFrame),
+    Plan(String),
+    Search(String),
+}
+
+impl TabularType {
+    /// What the tab shows, e.g. the table name or the query.
+    pub fn description(&self) -> &str {
+        match self {
+            TabularType::Help => "Help",
+            TabularType::Schema => "Schema",
+            TabularType::Errors => "Errors",
+            TabularType::Metadata(name) => name,
+            TabularType::Name(name) => name,
+            TabularType::Query(query) => query,
+            TabularType::Aggregation(description, _) => description,
+            TabularType::Pivot(description, _) => description,
+            TabularType::Plan(query) => query,
+            TabularType::Search(text) => text,
+        }
+    }
+}
+
+/// Cells of the column satisfying the comparison are drawn in the color.
+#[derive(Debug, Clone)]
+struct Highlight {
+    column: String,
+    op: String,
+    value: String,
+    color: Color,
 }
 
 #[derive(Debug)]
 pub struct Tabular {
     offset: usize,
     select: usize,
+    col_offset: usize,
+    col_select: usize,
+    hidden_columns: HashSet<String>,
+    bars: HashMap<String, Vec<Option<f64>>>,
+    highlights: Vec<Highlight>,
+    highlight_colors: HashMap<String, Vec<Option<Color>>>,
+    bookmarks: BTreeSet<usize>,
+    find: Option<(String, String)>,
     rendered_rows: u16,
+    scrolloff: usize,
     widths: Vec<usize>,
+    width_percents: HashMap<String, u16>,
     headers: Vec<String>,
+    labels: Vec<String>,
     table_values: TableValues,
+    format: ValueFormat,
     data_frame: DataFrame,
     state: TabularState,
     tabular_type: TabularType,
+    theme: Option<AppTheme>,
+    preview: bool,
+    read_options: Option<ReadOptions>,
+    loaded_at: DateTime<Local>,
+    modified: Option<SystemTime>,
+    show_footer: bool,
+    footer: Option<(usize, String)>,
+    totals: Option<Vec<String>>,
+    show_gutter: bool,
+    show_missing: bool,
+    header_mode: HeaderMode,
+    dense: bool,
+    compare: bool,
 }
 
 impl Tabular {
     /// Constructs a new instance of [`App`].
     pub fn new(data_frame: DataFrame, reset: TabularType) -> Self {
+        let format = ValueFormat::default();
+        let headers = data_frame
+            .get_column_names()
+            .into_iter()
+            .map(ToOwned::to_owned)
+            .collect_vec();
+        let table_values = TableValues::from_dataframe(&data_frame, &format);
         Self {
             offset: 0,
             select: 0,
+            col_offset: 0,
+            col_select: 0,
+            hidden_columns: HashSet::new(),
+            bars: HashMap::new(),
+            highlights: Vec::new(),
+            highlight_colors: HashMap::new(),
+            bookmarks: BTreeSet::new(),
+            find: None,
             rendered_rows: 0,
-            widths: data_frame_widths(&data_frame),
-            headers: data_frame
-                .get_column_names()
-                .into_iter()
-                .map(ToOwned::to_owned)
-                .collect(),
-            table_values: TableValues::from_dataframe(&data_frame),
+            scrolloff: 0,
+            widths: table_values.widths(&headers),
+            width_percents: HashMap::new(),
+            labels: headers.clone(),
+            headers,
+            table_values,
+            format,
             data_frame,
             state: TabularState::Table,
             tabular_type: reset,
+            theme: None,
+            preview: false,
+            read_options: None,
+            loaded_at: Local::now(),
+            modified: None,
+            show_footer: false,
+            footer: None,
+            totals: None,
+            show_gutter: false,
+            show_missing: false,
+            header_mode: HeaderMode::default(),
+            dense: false,
+            compare: false,
         }
     }
 
+    /// Marks the tabular as holding a partial preview of its source.
+    pub fn with_preview(mut self, preview: bool) -> Self {
+        self.preview = preview;
+        self
+    }
+
+    /// Sets the options the tabular's source file was read with.
+    pub fn with_read_options(mut self, read_options: ReadOptions) -> Self {
+        self.read_options = Some(read_options);
+        self
+    }
+
     /// Handles the tick event of the terminal.
     pub fn tick(&mut self) -> AppResult<()> {
         Ok(())
@@ -93,6 +237,344 @@ impl Tabular {
         Ok(())
     }
 
+    pub fn toggle_bookmark(&mut self) -> AppResult<()> {
+        if !self.bookmarks.remove(&self.select) {
+            self.bookmarks.insert(self.select);
+        }
+        Ok(())
+    }
+
+    pub fn select_next_bookmark(&mut self) -> AppResult<()> {
+        let next = self
+            .bookmarks
+            .range(self.select.saturating_add(1)..)
+            .next()
+            .or(self.bookmarks.first())
+            .copied()
+            .ok_or("No bookmarked row")?;
+        self.select(next)
+    }
+
+    pub fn select_prev_bookmark(&mut self) -> AppResult<()> {
+        let prev = self
+            .bookmarks
+            .range(..self.select)
+            .next_back()
+            .or(self.bookmarks.last())
+            .copied()
+            .ok_or("No bookmarked row")?;
+        self.select(prev)
+    }
+
+    /// Searches the focused column for values containing the text, ignoring
+    /// case, and selects the first match after the selected row. Returns the
+    /// position of the match and the number of matches.
+    pub fn find_in_column(&mut self, text: &str) -> AppResult<(usize, usize)> {
+        let column = self
+            .headers
+            .get(self.col_select)
+            .ok_or("No column to search")?
+            .to_owned();
+        self.find = Some((column, text.to_lowercase()));
+        self.select_next_match()
+    }
+
+    pub fn select_next_match(&mut self) -> AppResult<(usize, usize)> {
+        let matches = self.find_matches()?;
+        let next = matches
+            .iter()
+            .position(|row| *row > self.select)
+            .unwrap_or_default();
+        self.select(matches[next])?;
+        Ok((next + 1, matches.len()))
+    }
+
+    pub fn select_prev_match(&mut self) -> AppResult<(usize, usize)> {
+        let matches = self.find_matches()?;
+        let prev = matches
+            .iter()
+            .rposition(|row| *row < self.select)
+            .unwrap_or(matches.len() - 1);
+        self.select(matches[prev])?;
+        Ok((prev + 1, matches.len()))
+    }
+
+    /// Rows matching the last search, in order; never empty.
+    fn find_matches(&self) -> AppResult<Vec<usize>> {
+        let (column, text) = self.find.as_ref().ok_or("No search; use :find <text>")?;
+        let series = self
+            .data_frame
+            .column(column)
+            .map_err(|_| format!("Column '{}' not found", column))?;
+        let matches = series
+            .iter()
+            .positions(|value| {
+                !value.is_null() && any_value_into_string(value).to_lowercase().contains(text)
+            })
+            .collect_vec();
+        if matches.is_empty() {
+            Err(format!("No value of '{}' contains '{}'", column, text))?
+        }
+        Ok(matches)
+    }
+
+    /// The visible columns of the data frame, limited to the bookmarked rows
+    /// if there are any.
+    pub fn view_data_frame(&self) -> AppResult<DataFrame> {
+        let data_frame = DataFrame::new(
+            self.visible_columns()
+                .into_iter()
+                .map(|idx| self.data_frame.get_columns()[idx].clone())
+                .collect(),
+        )?;
+        if self.bookmarks.is_empty() {
+            Ok(data_frame)
+        } else {
+            let rows = self.bookmarks.iter().map(|row| *row as IdxSize).collect();
+            Ok(data_frame.take(&IdxCa::from_vec("rows", rows))?)
+        }
+    }
+
+    pub fn bookmarks(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn select_left(&mut self, len: usize) -> AppResult<()> {
+        let visible = self.visible_columns();
+        let pos = visible
+            .iter()
+            .position(|col| *col == self.col_select)
+            .unwrap_or_default();
+        self.select_column(visible.get(pos.saturating_sub(len)).copied().unwrap_or(0))
+    }
+
+    pub fn select_right(&mut self, len: usize) -> AppResult<()> {
+        let visible = self.visible_columns();
+        let pos = visible
+            .iter()
+            .position(|col| *col == self.col_select)
+            .unwrap_or_default();
+        self.select_column(
+            visible
+                .get(pos.saturating_add(len).min(visible.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or(0),
+        )
+    }
+
+    pub fn select_column(&mut self, col: usize) -> AppResult<()> {
+        self.col_select = col.min(self.headers.len().saturating_sub(1));
+        Ok(())
+    }
+
+    /// Focuses the column matching `name`, showing it if it is hidden.
+    pub fn goto_column(&mut self, name: &str) -> AppResult<()> {
+        let col = match_column(&self.headers, name)?;
+        self.hidden_columns.remove(&self.headers[col]);
+        self.select_column(col)
+    }
+
+    pub fn rename_column(&mut self, old: &str, new: &str) -> AppResult<()> {
+        if self.headers.iter().any(|header| header == new) {
+            Err(format!("Column '{}' already exists", new))?
+        }
+        let col = self
+            .headers
+            .iter()
+            .position(|header| header == old)
+            .ok_or_else(|| format!("Column '{}' not found", old))?;
+        self.data_frame.rename(old, new)?;
+        if self.hidden_columns.remove(old) {
+            self.hidden_columns.insert(new.to_owned());
+        }
+        if let Some(bar) = self.bars.remove(old) {
+            self.bars.insert(new.to_owned(), bar);
+        }
+        if let Some(base) = self.format.bases.remove(old) {
+            self.format.bases.insert(new.to_owned(), base);
+        }
+        if let Some(format) = self.format.date_formats.remove(old) {
+            self.format.date_formats.insert(new.to_owned(), format);
+        }
+        if let Some(percent) = self.width_percents.remove(old) {
+            self.width_percents.insert(new.to_owned(), percent);
+        }
+        if let Some((column, _)) = self.find.as_mut().filter(|(column, _)| column == old) {
+            *column = new.to_owned();
+        }
+        self.highlights
+            .iter_mut()
+            .filter(|highlight| highlight.column == old)
+            .for_each(|highlight| highlight.column = new.to_owned());
+        self.refresh_highlights();
+        self.headers[col] = new.to_owned();
+        self.refresh_labels();
+        self.footer = None;
+        Ok(())
+    }
+
+    pub fn toggle_column(&mut self) -> AppResult<()> {
+        let Some(header) = self.headers.get(self.col_select) else {
+            return Ok(());
+        };
+        if !self.hidden_columns.remove(header) {
+            if self.visible_columns().len() <= 1 {
+                return Err("Cannot hide the last visible column".into());
+            }
+            self.hidden_columns.insert(header.to_owned());
+            self.focus_visible_column();
+        }
+        Ok(())
+    }
+
+    /// Toggles drawing a bar beside each value of the numeric column, sized
+    /// by the value between the minimum and maximum of the column.
+    pub fn toggle_bar(&mut self, name: &str) -> AppResult<()> {
+        let col = match_column(&self.headers, name)?;
+        let header = self.headers[col].to_owned();
+        if self.bars.remove(&header).is_none() {
+            let fractions = bar_fractions(&self.data_frame[col])
+                .ok_or_else(|| format!("Column '{}' is not numeric", header))?;
+            self.bars.insert(header, fractions);
+        }
+        self.refresh_labels();
+        Ok(())
+    }
+
+    /// Adds a rule drawing the cells of the column satisfying the comparison
+    /// in the color; later rules take precedence.
+    pub fn add_highlight(
+        &mut self,
+        name: &str,
+        op: &str,
+        value: &str,
+        color: Color,
+    ) -> AppResult<()> {
+        let col = match_column(&self.headers, name)?;
+        compare_mask(&self.data_frame[col], op, value)?;
+        self.highlights.push(Highlight {
+            column: self.headers[col].to_owned(),
+            op: op.to_owned(),
+            value: value.to_owned(),
+            color,
+        });
+        self.refresh_highlights();
+        Ok(())
+    }
+
+    /// Removes the highlight rules of the column, or every rule.
+    pub fn reset_highlights(&mut self, name: Option<&str>) -> AppResult<()> {
+        match name {
+            Some(name) => {
+                let header = self.headers[match_column(&self.headers, name)?].to_owned();
+                self.highlights
+                    .retain(|highlight| highlight.column != header);
+            }
+            None => self.highlights.clear(),
+        }
+        self.refresh_highlights();
+        Ok(())
+    }
+
+    fn refresh_highlights(&mut self) {
+        let mut colors = HashMap::<String, Vec<Option<Color>>>::new();
+        for highlight in &self.highlights {
+            let Some(mask) = self
+                .data_frame
+                .column(&highlight.column)
+                .ok()
+                .and_then(|series| compare_mask(series, &highlight.op, &highlight.value).ok())
+            else {
+                continue;
+            };
+            let cells = colors
+                .entry(highlight.column.to_owned())
+                .or_insert_with(|| vec![None; mask.len()]);
+            for (cell, matched) in cells.iter_mut().zip(mask) {
+                if matched {
+                    *cell = Some(highlight.color);
+                }
+            }
+        }
+        self.highlight_colors = colors;
+    }
+
+    pub fn show_all_columns(&mut self) -> AppResult<()> {
+        self.hidden_columns.clear();
+        Ok(())
+    }
+
+    pub fn hidden_columns(&self) -> usize {
+        self.headers
+            .iter()
+            .filter(|header| self.hidden_columns.contains(*header))
+            .count()
+    }
+
+    pub fn selected_column(&self) -> usize {
+        self.col_select
+    }
+
+    /// Keeps the rows with the same value as the focused cell in the focused column.
+    pub fn filter_by_selected_cell(&mut self) -> AppResult<()> {
+        if self.col_select < self.data_frame.width() && self.select < self.data_frame.height() {
+            self.set_data_frame(filter_equal(&self.data_frame, self.col_select, self.select)?)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Header and raw value of the focused cell.
+    pub fn selected_cell(&self) -> Option<(String, String)> {
+        let series = self.data_frame.get_columns().get(self.col_select)?;
+        Some((
+            series.name().to_owned(),
+            series
+                .get(self.select)
+                .map(any_value_into_string)
+                .unwrap_or_default(),
+        ))
+    }
+
+    pub fn selected_column_name(&self) -> Option<&str> {
+        self.headers.get(self.col_select).map(String::as_str)
+    }
+
+    fn visible_columns(&self) -> Vec<usize> {
+        self.headers
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, header)| (!self.hidden_columns.contains(header)).then_some(idx))
+            .collect()
+    }
+
+    fn focus_visible_column(&mut self) {
+        let visible = self.visible_columns();
+        self.col_select = visible
+            .iter()
+            .find(|col| **col >= self.col_select)
+            .or(visible.last())
+            .copied()
+            .unwrap_or(0);
+    }
+
+    fn adjust_col_offset(&mut self, columns: &[usize], widths: &[usize], width: u16) {
+        let pos = columns
+            .iter()
+            .position(|col| *col == self.col_select)
+            .unwrap_or_default();
+        self.col_offset = self.col_offset.min(pos);
+        while self.col_offset < pos
+            && columns[self.col_offset..=pos]
+                .iter()
+                .map(|col| widths[*col] + self.column_spacing())
+                .sum::<usize>()
+                > width as usize + self.column_spacing()
+        {
+            self.col_offset += 1;
+        }
+    }
+
     pub fn scroll_up(&mut self) -> AppResult<()> {
         if let TabularState::Sheet(scroll) = &mut self.state {
             scroll.up();
@@ -115,14 +597,69 @@ impl Tabular {
         self.rendered_rows.into()
     }
 
+    /// Scrolls so the selected row is visible with at least `scrolloff` rows
+    /// above and below it, except at the top and bottom of the table.
     pub fn adjust_offset(&mut self) {
+        let rows = usize::from(self.rendered_rows).max(1);
+        let margin = self.scrolloff.min((rows - 1) / 2);
+        let max_offset = self.data_frame.height().saturating_sub(rows);
         self.offset = self.offset.clamp(
-            self.select
-                .saturating_sub(self.rendered_rows.saturating_sub(1).into()),
-            self.select,
+            (self.select + margin + 1).saturating_sub(rows).min(max_offset),
+            self.select.saturating_sub(margin).min(max_offset),
         );
     }
 
+    pub fn set_scrolloff(&mut self, scrolloff: usize) {
+        self.scrolloff = scrolloff;
+    }
+
+    /// Sets the widths of columns as percentages of the table width, for the
+    /// ones this tabular has now or after a reset.
+    pub fn set_column_widths(&mut self, widths: &[(String, u16)]) {
+        self.width_percents.extend(widths.iter().cloned());
+    }
+
+    /// Sets the width of the column as a percentage of the table width, or
+    /// back to fitting its values when `None`.
+    pub fn set_column_width(&mut self, column: &str, percent: Option<u16>) -> AppResult<()> {
+        if !self.headers.iter().any(|header| header == column) {
+            Err(format!("Column '{}' not found", column))?
+        }
+        let Some(percent) = percent else {
+            self.width_percents.remove(column);
+            return Ok(());
+        };
+        if !(1..=100).contains(&percent) {
+            Err(format!("Invalid percentage {}", percent))?
+        }
+        let total = percent
+            + self
+                .headers
+                .iter()
+                .filter(|header| *header != column)
+                .filter_map(|header| self.width_percents.get(header))
+                .sum::<u16>();
+        if total > 100 {
+            Err(format!("Column widths would add up to {}%", total))?
+        }
+        self.width_percents.insert(column.to_owned(), percent);
+        Ok(())
+    }
+
+    /// Widths of the columns in a table `width` wide, where columns with a
+    /// percentage get that share of it, spacing included, and others fit
+    /// their values.
+    fn layout_widths(&self, width: u16) -> Vec<usize> {
+        izip!(&self.headers, &self.widths)
+            .map(|(header, fit)| match self.width_percents.get(header) {
+                Some(percent) => (usize::from(width) * usize::from(*percent) / 100)
+                    .saturating_sub(self.column_spacing())
+                    .max(1),
+                None => *fit,
+            })
+            .collect()
+    }
+
     pub fn switch_view(&mut self) -> AppResult<()> {
         match self.state {
             TabularState::Table => self.show_sheet(),
@@ -140,17 +677,256 @@ impl Tabular {
         Ok(())
     }
 
+    /// Toggles showing the records before and after the selected one beside
+    /// it in sheet view.
+    pub fn toggle_compare(&mut self) -> AppResult<()> {
+        self.compare = !self.compare;
+        Ok(())
+    }
+
+    pub fn toggle_gutter(&mut self) -> AppResult<()> {
+        self.show_gutter = !self.show_gutter;
+        Ok(())
+    }
+
+    pub fn toggle_missing(&mut self) -> AppResult<()> {
+        self.show_missing = !self.show_missing;
+        self.refresh_labels();
+        Ok(())
+    }
+
+    /// Toggles dense mode, which keeps the header to a single line and
+    /// narrows the spacing between columns to fit more of the table.
+    pub fn toggle_dense(&mut self) -> AppResult<()> {
+        self.dense = !self.dense;
+        self.refresh_labels();
+        Ok(())
+    }
+
+    fn column_spacing(&self) -> usize {
+        if self.dense {
+            1
+        } else {
+            2
+        }
+    }
+
+    fn gutter_width(&self) -> usize {
+        if self.show_gutter {
+            self.table_values.height().to_string().len()
+        } else {
+            0
+        }
+    }
+
+    pub fn toggle_footer(&mut self) -> AppResult<()> {
+        self.show_footer = !self.show_footer;
+        Ok(())
+    }
+
+    /// Toggles a row of the sums of the numeric columns, pinned under the
+    /// table like the header above it.
+    pub fn toggle_totals(&mut self) -> AppResult<()> {
+        self.totals = match self.totals {
+            Some(_) => None,
+            None => Some(column_totals(&self.data_frame)),
+        };
+        self.refresh_labels();
+        Ok(())
+    }
+
+    fn footer_text(&mut self) -> &str {
+        if !matches!(self.footer, Some((col, _)) if col == self.col_select) {
+            let text = self
+                .data_frame
+                .get_columns()
+                .get(self.col_select)
+                .map(|series| {
+                    std::iter::once(format!("{}:", series.name()))
+                        .chain(
+                            series_summary(series)
+                                .into_iter()
+                                .map(|(name, value)| format!("{} {}", name, value)),
+                        )
+                        .join("  ")
+                })
+                .unwrap_or_default();
+            self.footer = Some((self.col_select, text));
+        }
+        self.footer
+            .as_ref()
+            .map(|(_, text)| text.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn toggle_thousands(&mut self) -> AppResult<()> {
+        self.format.thousands = !self.format.thousands;
+        self.refresh_values();
+        Ok(())
+    }
+
+    /// Cycles the base the integers of the focused column are shown in,
+    /// returning the column and its new base.
+    pub fn cycle_int_base(&mut self) -> AppResult<(String, IntBase)> {
+        let column = self
+            .selected_column_name()
+            .ok_or("No column to change the base of")?
+            .to_owned();
+        if !self.data_frame.column(&column)?.dtype().is_integer() {
+            Err(format!("Column '{}' is not an integer column", column))?
+        }
+        let base = self
+            .format
+            .bases
+            .get(&column)
+            .copied()
+            .unwrap_or_default()
+            .next();
+        if base == IntBase::Decimal {
+            self.format.bases.remove(&column);
+        } else {
+            self.format.bases.insert(column.clone(), base);
+        }
+        self.refresh_values();
+        Ok((column, base))
+    }
+
+    /// Sets the strftime format the dates and times of all columns are shown
+    /// in, or back to the default when `None`, keeping the formats of columns.
+    pub fn set_date_format(&mut self, format: Option<String>) {
+        self.format.date_format = format;
+        self.refresh_values();
+    }
+
+    /// Sets the strftime format the dates and times of the column are shown
+    /// in, or back to the one of all columns when `None`.
+    pub fn set_column_date_format(
+        &mut self,
+        column: &str,
+        format: Option<String>,
+    ) -> AppResult<()> {
+        if !self.headers.iter().any(|header| header == column) {
+            Err(format!("Column '{}' not found", column))?
+        }
+        if !self.data_frame.column(column)?.dtype().is_temporal() {
+            Err(format!("Column '{}' is not a date or time column", column))?
+        }
+        match format {
+            Some(format) => self.format.date_formats.insert(column.to_owned(), format),
+            None => self.format.date_formats.remove(column),
+        };
+        self.refresh_values();
+        Ok(())
+    }
+
+    pub fn cycle_bool_glyphs(&mut self) -> AppResult<()> {
+        self.format.bools = self.format.bools.next();
+        self.refresh_values();
+        Ok(())
+    }
+
+    fn refresh_values(&mut self) {
+        self.table_values
+            .replace_dataframe(&self.data_frame, &self.format);
+        self.refresh_labels();
+    }
+
+    /// Recomputes the header labels, annotated with the share of missing
+    /// values when enabled and fitted to the header mode, and the column
+    /// widths, widened for the bars of columns in bar mode and the totals.
+    fn refresh_labels(&mut self) {
+        self.fit_labels();
+        for (header, width) in izip!(&self.headers, self.widths.iter_mut()) {
+            if self.bars.contains_key(header) {
+                *width += BAR_WIDTH + 1;
+            }
+        }
+        if let Some(totals) = &self.totals {
+            for (total, width) in izip!(totals, self.widths.iter_mut()) {
+                *width = (*width).max(total.width());
+            }
+        }
+    }
+
+    fn fit_labels(&mut self) {
+        let labels = if self.show_missing {
+            izip!(&self.headers, self.data_frame.get_columns())
+                .map(|(header, series)| format!("{} ({})", header, missing_annotation(series)))
+                .collect_vec()
+        } else {
+            self.headers.clone()
+        };
+        let lines = match self.header_mode {
+            HeaderMode::Full => {
+                self.widths = self.table_values.widths(&labels);
+                self.labels = labels;
+                return;
+            }
+            HeaderMode::Wrap if !self.dense => 2,
+            HeaderMode::Truncate | HeaderMode::Wrap => 1,
+        };
+        self.widths = izip!(
+            self.table_values.widths(&vec![String::new(); labels.len()]),
+            &labels
+        )
+        .map(|(width, label)| {
+            width.max(
+                label
+                    .chars()
+                    .count()
+                    .div_ceil(lines)
+                    .min(HEADER_MIN_WIDTH * lines),
+            )
+        })
+        .collect();
+        self.labels = izip!(&labels, &self.widths)
+            .map(|(label, width)| fit_text(label, *width, lines))
+            .collect();
+    }
+
+    pub fn cycle_header_mode(&mut self) -> AppResult<()> {
+        self.header_mode = self.header_mode.next();
+        self.refresh_labels();
+        Ok(())
+    }
+
+    fn header_height(&self) -> u16 {
+        if self.dense {
+            return 1;
+        }
+        self.labels
+            .iter()
+            .map(|label| label.lines().count())
+            .max()
+            .unwrap_or(1)
+            .max(1) as u16
+    }
+
     pub fn set_data_frame(&mut self, data_frame: DataFrame) -> AppResult<()> {
-        self.widths = data_frame_widths(&data_frame);
         self.offset = 0;
         self.select = 0;
+        self.col_offset = 0;
+        self.bookmarks.clear();
         self.headers = data_frame
             .get_column_names()
             .into_iter()
             .map(ToOwned::to_owned)
             .collect();
-        self.table_values.replace_dataframe(&data_frame);
+        self.focus_visible_column();
+        self.footer = None;
+        self.bars = std::mem::take(&mut self.bars)
+            .into_keys()
+            .filter_map(|name| {
+                let fractions = bar_fractions(data_frame.column(&name).ok()?)?;
+                Some((name, fractions))
+            })
+            .collect();
+        if self.totals.is_some() {
+            self.totals = Some(column_totals(&data_frame));
+        }
         self.data_frame = data_frame;
+        self.refresh_highlights();
+        self.refresh_values();
         Ok(())
     }
 
@@ -174,6 +950,49 @@ impl Tabular {
         &self.tabular_type
     }
 
+    pub fn set_tabular_type(&mut self, tabular_type: TabularType) {
+        self.tabular_type = tabular_type;
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.preview
+    }
+
+    pub fn set_preview(&mut self, preview: bool) -> AppResult<()> {
+        self.preview = preview;
+        Ok(())
+    }
+
+    pub fn read_options(&self) -> Option<&ReadOptions> {
+        self.read_options.as_ref()
+    }
+
+    /// When the data of the tabular was last read from its source.
+    pub fn loaded_at(&self) -> DateTime<Local> {
+        self.loaded_at
+    }
+
+    pub fn mark_loaded(&mut self) {
+        self.loaded_at = Local::now();
+    }
+
+    /// Records the modification time of the source file, returning whether it
+    /// changed since the previously recorded one.
+    pub fn update_modified(&mut self, modified: SystemTime) -> bool {
+        self.modified
+            .replace(modified)
+            .is_some_and(|previous| previous != modified)
+    }
+
+    pub fn theme(&self) -> Option<AppTheme> {
+        self.theme
+    }
+
+    pub fn set_theme(&mut self, theme: Option<AppTheme>) -> AppResult<()> {
+        self.theme = theme;
+        Ok(())
+    }
+
     pub fn render<Theme: Styler>(
         &mut self,
         frame: &mut Frame,
@@ -182,8 +1001,54 @@ impl Tabular {
     ) -> AppResult<()> {
         match &mut self.state {
             TabularState::Table => {
-                self.rendered_rows = layout.height.saturating_sub(1);
+                let layout = if self.show_footer {
+                    let [table, footer] =
+                        Layout::vertical([Constraint::Fill(1), Constraint::Length(1)])
+                            .areas(layout);
+                    frame.render_widget(
+                        Line::raw(self.footer_text().to_owned()).style(Theme::table_header()),
+                        footer,
+                    );
+                    table
+                } else {
+                    layout
+                };
+                self.rendered_rows = layout
+                    .height
+                    .saturating_sub(self.header_height())
+                    .saturating_sub(self.totals.is_some() as u16);
                 self.adjust_offset();
+                let visible = self.visible_columns();
+                let gutter = self.gutter_width();
+                let spacing = self.column_spacing();
+                let width = layout.width.saturating_sub(if gutter > 0 {
+                    (gutter + spacing) as u16
+                } else {
+                    0
+                });
+                let widths = self.layout_widths(width);
+                self.adjust_col_offset(&visible, &widths, width);
+                let columns = &visible[self.col_offset.min(visible.len())..];
+                let bars = self
+                    .headers
+                    .iter()
+                    .map(|header| self.bars.get(header).map(Vec::as_slice))
+                    .collect_vec();
+                let highlights = self
+                    .headers
+                    .iter()
+                    .map(|header| self.highlight_colors.get(header).map(Vec::as_slice))
+                    .collect_vec();
+                let bools = self
+                    .data_frame
+                    .get_columns()
+                    .iter()
+                    .map(|series| {
+                        (series.dtype() == &DataType::Boolean
+                            && self.format.bools != BoolGlyphs::Text)
+                            .then_some(self.format.bools)
+                    })
+                    .collect_vec();
 
                 if selection {
                     let mut local_st = TableState::new()
@@ -193,8 +1058,17 @@ impl Tabular {
                     frame.render_stateful_widget(
                         tabulate::<Theme>(
                             &self.table_values,
-                            &self.widths,
-                            &self.headers,
+                            &widths,
+                            &self.labels,
+                            &bars,
+                            &highlights,
+                            &bools,
+                            self.totals.as_deref(),
+                            columns,
+                            self.col_select,
+                            &self.bookmarks,
+                            gutter,
+                            self.dense,
                             self.offset,
                             self.rendered_rows as usize,
                         ),
@@ -205,8 +1079,17 @@ impl Tabular {
                     frame.render_widget(
                         tabulate::<Theme>(
                             &self.table_values,
-                            &self.widths,
-                            &self.headers,
+                            &widths,
+                            &self.labels,
+                            &bars,
+                            &highlights,
+                            &bools,
+                            self.totals.as_deref(),
+                            columns,
+                            self.col_select,
+                            &self.bookmarks,
+                            gutter,
+                            self.dense,
                             self.offset,
                             self.rendered_rows as usize,
                         ),
@@ -214,16 +1097,50 @@ impl Tabular {
                     );
                 }
             }
+            TabularState::Sheet(scroll) if self.compare => {
+                self.rendered_rows = 0;
+                let space = layout.inner(Margin::new(1, 1));
+                let title = format!(" {} ", self.select + 1);
+
+                let records = (self.select.saturating_sub(1)
+                    ..(self.select + 2).min(self.data_frame.height()))
+                    .map(|row| (row, raw_row(&self.data_frame, row)))
+                    .collect_vec();
+                let fields = self
+                    .headers
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, header)| !self.hidden_columns.contains(*header))
+                    .collect_vec();
+
+                scroll.adjust(fields.len(), space.height.saturating_sub(1) as usize);
+                frame.render_widget(
+                    compare_table::<Theme>(
+                        &title,
+                        &fields,
+                        &records,
+                        self.select,
+                        scroll.to_u16() as usize,
+                    ),
+                    layout,
+                );
+            }
             TabularState::Sheet(scroll) => {
                 self.rendered_rows = 0;
                 let space = layout.inner(Margin::new(1, 1));
                 let title = format!(" {} ", self.select + 1);
 
-                let values = self.table_values.get_row(self.select);
+                let raw_values = raw_row(&self.data_frame, self.select);
+                let (headers, values): (Vec<_>, Vec<_>) = self
+                    .headers
+                    .iter()
+                    .zip(raw_values.iter().map(String::as_str))
+                    .filter(|(header, _)| !self.hidden_columns.contains(*header))
+                    .unzip();
 
                 let (paragraph, line_count) = paragraph_from_headers_values::<Theme>(
                     &title,
-                    &self.headers,
+                    &headers,
                     &values,
                     space.width,
                 );
@@ -236,9 +1153,22 @@ impl Tabular {
     }
 }
 
+fn raw_row(data_frame: &DataFrame, row: usize) -> Vec<String> {
+    data_frame
+        .get_columns()
+        .iter()
+        .map(|series| {
+            series
+                .get(row)
+                .map(any_value_into_pretty_string)
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
 fn paragraph_from_headers_values<'a, Theme: Styler>(
     title: &'a str,
-    headers: &'a [String],
+    headers: &'a [&String],
     values: &'a [&str],
     width: u16,
 ) -> (Paragraph<'a>, usize) {
@@ -254,7 +1184,7 @@ fn paragraph_from_headers_values<'a, Theme: Styler>(
         .block(Block::new().title(title).borders(Borders::ALL))
         .style(Theme::sheet_block())
         .alignment(Alignment::Left)
-        .wrap(Wrap { trim: true });
+        .wrap(Wrap { trim: false });
     (prgr, lc)
 }
 
@@ -276,39 +1206,205 @@ fn lines_from_header_value<'a, Theme: Styler>(
         .collect_vec()
 }
 
+/// Table with a row per field and a column per record, where the values of
+/// the other records that differ from the selected one are in bold.
+fn compare_table<'a, Theme: Styler>(
+    title: &'a str,
+    fields: &[(usize, &'a String)],
+    records: &[(usize, Vec<String>)],
+    selected: usize,
+    skip: usize,
+) -> Table<'a> {
+    let current = records
+        .iter()
+        .find(|(row, _)| *row == selected)
+        .map(|(_, values)| values.as_slice())
+        .unwrap_or_default();
+    let rows = fields.iter().skip(skip).map(|(col, header)| {
+        Row::new(
+            std::iter::once(Cell::new(header.as_str()).style(Theme::table_header_cell(*col)))
+                .chain(records.iter().map(|(row, values)| {
+                    let value = values.get(*col).map(String::as_str).unwrap_or_default();
+                    let style = if *row == selected {
+                        Theme::table_highlight()
+                    } else if current.get(*col).map(String::as_str) != Some(value) {
+                        Theme::sheet_value().bold()
+                    } else {
+                        Theme::sheet_value()
+                    };
+                    Cell::new(value.lines().next().unwrap_or_default().to_owned()).style(style)
+                }))
+                .collect_vec(),
+        )
+    });
+    let header_width = fields
+        .iter()
+        .map(|(_, header)| header.chars().count())
+        .max()
+        .unwrap_or_default();
+    Table::new(
+        rows,
+        std::iter::once(Constraint::Length(header_width as u16))
+            .chain(records.iter().map(|_| Constraint::Fill(1)))
+            .collect_vec(),
+    )
+    .header(
+        Row::new(
+            std::iter::once(Cell::default())
+                .chain(
+                    records
+                        .iter()
+                        .map(|(row, _)| Cell::new(format!("#{}", row + 1))),
+                )
+                .collect_vec(),
+        )
+        .style(Theme::table_header()),
+    )
+    .block(Block::new().title(title).borders(Borders::ALL))
+    .style(Theme::sheet_block())
+    .column_spacing(2)
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn tabulate<'a, Theme: Styler>(
     value_pool: &'a TableValues,
     widths: &'a [usize],
     headers: &'a [String],
+    bars: &[Option<&[Option<f64>]>],
+    highlights: &[Option<&[Option<Color>]>],
+    bools: &[Option<BoolGlyphs>],
+    totals: Option<&'a [String]>,
+    columns: &'a [usize],
+    focused: usize,
+    bookmarks: &BTreeSet<usize>,
+    gutter: usize,
+    dense: bool,
     offset: usize,
     length: usize,
 ) -> Table<'a> {
-    Table::new(
+    let table = Table::new(
         (offset..offset + length)
             .map(|row_idx| {
-                Row::new(value_pool.get_row(row_idx).into_iter().map(Cell::new))
-                    .style(Theme::table_row(row_idx))
+                let number = (gutter > 0 && row_idx < value_pool.height()).then(|| {
+                    Cell::new(format!("{:>gutter$}", row_idx + 1)).style(Theme::table_gutter())
+                });
+                Row::new(number.into_iter().chain(columns.iter().map(|col_idx| {
+                    value_cell::<Theme>(
+                        value_pool.get(row_idx, *col_idx).unwrap_or(""),
+                        bars[*col_idx].map(|bar| bar.get(row_idx).copied().flatten()),
+                        highlights[*col_idx]
+                            .and_then(|colors| colors.get(row_idx).copied().flatten()),
+                        bools[*col_idx],
+                        *col_idx,
+                    )
+                })))
+                .style(if bookmarks.contains(&row_idx) {
+                    Theme::table_bookmark()
+                } else {
+                    Theme::table_row(row_idx)
+                })
             })
             .collect_vec(),
-        widths
-            .iter()
-            .copied()
-            .map(|w| Constraint::Length(w as u16))
+        (gutter > 0)
+            .then_some(Constraint::Length(gutter as u16))
+            .into_iter()
+            .chain(
+                columns
+                    .iter()
+                    .map(|col_idx| Constraint::Length(widths[*col_idx] as u16)),
+            )
             .collect::<Vec<_>>(),
     )
-    .header(header_row::<Theme>(headers))
+    .header(header_row::<Theme>(
+        headers,
+        columns,
+        focused,
+        gutter > 0,
+        dense,
+    ))
     .highlight_style(Theme::table_highlight())
-    .column_spacing(2)
+    .column_spacing(if dense { 1 } else { 2 });
+    match totals {
+        Some(totals) => table.footer(totals_row::<Theme>(totals, columns, gutter > 0)),
+        None => table,
+    }
 }
 
-fn header_row<Theme: Styler>(df: &[String]) -> Row {
+fn totals_row<'a, Theme: Styler>(
+    totals: &'a [String],
+    columns: &'a [usize],
+    gutter: bool,
+) -> Row<'a> {
     Row::new(
-        df.iter()
-            .enumerate()
-            .map(|(col_idx, name)| {
-                Cell::new(name.as_str()).style(Theme::table_header_cell(col_idx))
-            })
+        gutter
+            .then(|| Cell::new("Σ"))
+            .into_iter()
+            .chain(
+                columns
+                    .iter()
+                    .map(|col_idx| Cell::new(totals[*col_idx].as_str())),
+            )
+            .collect_vec(),
+    )
+    .style(Theme::table_header())
+}
+
+/// Cell of the value, preceded by its bar if the column is in bar mode and
+/// drawn in the color of the highlight rule it satisfies, if any, or of the
+/// theme for boolean glyphs.
+fn value_cell<Theme: Styler>(
+    value: &str,
+    bar: Option<Option<f64>>,
+    highlight: Option<Color>,
+    glyphs: Option<BoolGlyphs>,
+    col: usize,
+) -> Cell<'_> {
+    let style = match (highlight, glyphs) {
+        (Some(color), _) => Style::new().fg(color).bold(),
+        (None, Some(glyphs)) if value == glyphs.glyph(true) => Theme::table_bool(true),
+        (None, Some(glyphs)) if value == glyphs.glyph(false) => Theme::table_bool(false),
+        _ => Style::default(),
+    };
+    match bar {
+        Some(fraction) => Cell::new(Line::from(vec![
+            Span::styled(bar_text(fraction, BAR_WIDTH), Theme::table_bar(col)),
+            Span::raw(" "),
+            Span::styled(value, style),
+        ])),
+        None => Cell::new(value).style(style),
+    }
+}
+
+fn header_row<'a, Theme: Styler>(
+    headers: &'a [String],
+    columns: &'a [usize],
+    focused: usize,
+    gutter: bool,
+    dense: bool,
+) -> Row<'a> {
+    Row::new(
+        gutter
+            .then(Cell::default)
+            .into_iter()
+            .chain(columns.iter().map(|col_idx| {
+                let style = Theme::table_header_cell(*col_idx);
+                Cell::new(headers[*col_idx].as_str()).style(if *col_idx == focused {
+                    style.reversed()
+                } else {
+                    style
+                })
+            }))
             .collect::<Vec<_>>(),
     )
+    .height(if dense {
+        1
+    } else {
+        columns
+            .iter()
+            .map(|col_idx| headers[*col_idx].lines().count())
+            .max()
+            .unwrap_or(1)
+            .max(1) as u16
+    })
     .style(Theme::table_header())
 }
@@ -1,12 +1,25 @@
-use clap::{Parser, ValueEnum};
+use clap::{ArgAction, Parser, ValueEnum};
 use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, args_override_self = true)]
 pub struct Args {
-    #[arg(help = "Path(s) to the file(s) to be opened.", required = true)]
+    #[arg(
+        help = "Path(s) to the file(s) to be opened. Without any, files can be opened with :open."
+    )]
     pub files: Vec<PathBuf>,
 
+    #[arg(
+        long,
+        help = "Read a file in the given format from the standard input, opened before any other file(s).",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub stdin: bool,
+
     #[arg(short, long, help = "Input file format",
         value_enum,
         default_value_t = Format::Dsv)]
@@ -15,14 +28,22 @@ pub struct Args {
     #[arg(
         long,
         help = "Specify if the DSV file does not contain a header row.",
-        default_value_t = false
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
     )]
     pub no_header: bool,
 
     #[arg(
         long,
         help = "If set, parsing errors while loading the DSV file will be ignored.",
-        default_value_t = false
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
     )]
     pub ignore_errors: bool,
 
@@ -37,19 +58,173 @@ pub struct Args {
 
     #[arg(
         long,
-        help = "Character used as the field separator or delimiter while loading the DSV file.",
+        help = "Preset of the separator and quote characters and of how rows with too many fields are handled while loading the DSV file: excel and excel-tab drop the extra fields, rfc4180 rejects them.",
+        required = false,
+        value_enum
+    )]
+    pub dialect: Option<Dialect>,
+
+    #[arg(
+        long,
+        help = "Character used as the field separator or delimiter while loading the DSV file. Overrides the dialect's separator. If neither is given, the separator is detected for each file.",
+        required = false
+    )]
+    pub separator: Option<char>,
+
+    #[arg(
+        long,
+        help = "Number of lines sampled to detect the separator of the DSV file when it is not given.",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..),
+        default_value_t = 16
+    )]
+    pub delimiter_inference_rows: usize,
+
+    #[arg(
+        long,
+        help = "Character used to quote fields while loading the DSV file. Overrides the dialect's quote character.",
+        required = false
+    )]
+    pub quote_char: Option<char>,
+
+    #[arg(
+        long,
+        help = "Line terminator used while loading the DSV file, e.g. '\\r' for classic Mac files. '\\r\\n' is handled by the default '\\n'.",
+        required = false,
+        value_parser = parse_char
+    )]
+    pub eol: Option<char>,
+
+    #[arg(
+        long,
+        help = "Thousands separator of numbers in the DSV file, e.g. ',' for 1,234.56, removed so that their columns are read as numbers. With '.', the decimal separator is ','.",
+        required = false
+    )]
+    pub thousands: Option<char>,
+
+    #[arg(
+        long,
+        help = "Only load the first row group of the Parquet file(s), or the first rows of the DSV file(s), for a quick preview.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub preview: bool,
+
+    #[arg(
+        long,
+        help = "Only show the columns of the Parquet file(s) and their data types, read from the file footer(s) without loading any data.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub schema_only: bool,
+
+    #[arg(
+        long,
+        help = "Files larger than this size (e.g. 512M, 2G) are loaded as a preview instead of in full.",
         required = false,
-        default_value_t = ','
+        env = "TABIEW_LIMIT_MEMORY",
+        value_parser = parse_size
     )]
-    pub separator: char,
+    pub limit_memory: Option<u64>,
 
     #[arg(
         long,
-        help = "Character used to quote fields while loading the DSV file.",
+        help = "Unnest nested objects (struct columns) into top-level columns with dotted names, e.g. address.city.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub flatten: bool,
+
+    #[arg(
+        long,
+        help = "JSON file mapping column names to data types (e.g. {\"zip\": \"str\"}) that are enforced instead of the inferred ones.",
+        required = false
+    )]
+    pub schema: Option<PathBuf>,
+
+    #[arg(
+        long,
+        help = "Minimum number of rows kept visible above and below the selected row.",
+        default_value_t = 2
+    )]
+    pub scrolloff: usize,
+
+    #[arg(
+        long,
+        help = "Widths of columns as percentages of the table width, e.g. 'name=30,notes=50'. Other columns fit their values in the remaining space.",
         required = false,
-        default_value_t = '"'
+        value_parser = parse_column_widths
     )]
-    pub quote_char: char,
+    pub column_widths: Option<ColumnWidths>,
+
+    #[arg(
+        long,
+        help = "strftime format dates and times are shown in, e.g. '%d/%m/%Y %H:%M'. Values lacking a field of the format, such as dates for '%H', keep the default format.",
+        required = false
+    )]
+    pub date_format: Option<String>,
+
+    #[arg(
+        long,
+        help = "SQL query run once the file(s) are loaded, whose result is shown instead of the raw table(s).",
+        required = false
+    )]
+    pub initial_sql: Option<String>,
+
+    #[arg(
+        long,
+        help = "Reload the file(s) whenever they are modified.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub watch: bool,
+
+    #[arg(
+        long,
+        help = "Render below the prompt instead of in the alternate screen, leaving the last view in the terminal scrollback on exit.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub inline: bool,
+
+    #[arg(
+        long,
+        help = "Close tabs and quit without asking for confirmation.",
+        default_value_t = false,
+        action = ArgAction::Set,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "true"
+    )]
+    pub no_confirm: bool,
+
+    #[arg(
+        long,
+        help = "Interval in milliseconds between checks for modified files and other periodic updates.",
+        default_value_t = 250
+    )]
+    pub tick_rate: u64,
+
+    #[arg(
+        long,
+        help = "TOML file of settings named after the long options (e.g. theme = \"argonaut\"), overridden by the options given on the command line, and of a [keybinds] table binding keys to commands (e.g. \"ctrl+g\" = \":goto 1\").",
+        required = false
+    )]
+    pub config: Option<PathBuf>,
 
     #[arg(
         long,
@@ -64,11 +239,19 @@ pub struct Args {
 #[derive(Debug, Clone, ValueEnum)]
 pub enum Format {
     Dsv,
+    Csv,
+    Tsv,
+    Psv,
     Parquet,
+    Json,
+    #[value(alias = "ndjson")]
+    Jsonl,
+    Ods,
 }
 
 #[derive(Debug, Clone, ValueEnum)]
 pub enum InferSchema {
+    #[value(alias = "none", alias = "off")]
     No,
     Fast,
     Full,
@@ -76,6 +259,13 @@ pub enum InferSchema {
 }
 
 #[derive(Debug, Clone, ValueEnum)]
+pub enum Dialect {
+    Excel,
+    ExcelTab,
+    Rfc4180,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
 pub enum AppTheme {
     Monokai,
     Argonaut,
@@ -92,3 +282,108 @@ impl From<&InferSchema> for Option<usize> {
         }
     }
 }
+
+impl Format {
+    /// Whether the format is read by the DSV reader.
+    pub fn is_dsv(&self) -> bool {
+        matches!(self, Format::Dsv | Format::Csv | Format::Tsv | Format::Psv)
+    }
+
+    /// Separator implied by the format name, if any.
+    pub fn separator(&self) -> Option<char> {
+        match self {
+            Format::Csv => Some(','),
+            Format::Tsv => Some('\t'),
+            Format::Psv => Some('|'),
+            _ => None,
+        }
+    }
+}
+
+impl Dialect {
+    pub fn separator(&self) -> char {
+        match self {
+            Dialect::Excel => ',',
+            Dialect::ExcelTab => '\t',
+            Dialect::Rfc4180 => ',',
+        }
+    }
+
+    pub fn quote_char(&self) -> char {
+        '"'
+    }
+
+    /// Whether rows with more fields than the header are cut short, as
+    /// spreadsheets do, instead of failing the read as RFC 4180 requires.
+    pub fn truncate_ragged_lines(&self) -> bool {
+        match self {
+            Dialect::Excel | Dialect::ExcelTab => true,
+            Dialect::Rfc4180 => false,
+        }
+    }
+}
+
+/// Parses a single character, or one of the `\n`, `\r`, and `\t` escapes.
+fn parse_char(value: &str) -> Result<char, String> {
+    match value {
+        "\\n" => Ok('\n'),
+        "\\r" => Ok('\r'),
+        "\\t" => Ok('\t'),
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Ok(c),
+                _ => Err(format!("Expected a single character, got '{}'", value)),
+            }
+        }
+    }
+}
+
+/// Widths of columns as percentages of the table width, by column name.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnWidths(pub Vec<(String, u16)>);
+
+/// Parses comma separated `column=percent` pairs, e.g. `name=30,notes=50%`.
+fn parse_column_widths(value: &str) -> Result<ColumnWidths, String> {
+    let widths = value
+        .split(',')
+        .map(|pair| {
+            let (column, percent) = pair
+                .rsplit_once('=')
+                .ok_or_else(|| format!("Expected 'column=percent', got '{}'", pair))?;
+            let percent = percent
+                .trim()
+                .trim_end_matches('%')
+                .parse::<u16>()
+                .ok()
+                .filter(|percent| (1..=100).contains(percent))
+                .ok_or_else(|| format!("Invalid percentage '{}'", percent))?;
+            Ok((column.trim().to_owned(), percent))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+    if widths.iter().map(|(_, percent)| percent).sum::<u16>() > 100 {
+        Err("Column widths add up to more than 100%")?
+    }
+    Ok(ColumnWidths(widths))
+}
+
+/// Parses a size in bytes with an optional binary suffix, e.g. `512K`, `1.5G`.
+fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let digits = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(value.len());
+    let (number, unit) = value.split_at(digits);
+    let number = number
+        .parse::<f64>()
+        .map_err(|_| format!("Invalid size '{}'", value))?;
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1_u64,
+        "K" | "KB" | "KIB" => 1 << 10,
+        "M" | "MB" | "MIB" => 1 << 20,
+        "G" | "GB" | "GIB" => 1 << 30,
+        "T" | "TB" | "TIB" => 1 << 40,
+        _ => return Err(format!("Invalid size unit '{}'", unit)),
+    };
+    Ok((number * multiplier as f64) as u64)
+}
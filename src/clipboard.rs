@@ -0,0 +1,29 @@
+use crate::app::AppResult;
+
+/// System clipboard handle.
+///
+/// The underlying clipboard is connected lazily on first use and kept alive
+/// afterwards, as some platforms (e.g. X11) drop the copied content along
+/// with the handle.
+#[derive(Default)]
+pub struct Clipboard {
+    inner: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    pub fn get_text(&mut self) -> AppResult<String> {
+        Ok(self.connect()?.get_text()?)
+    }
+
+    pub fn set_text(&mut self, text: impl Into<String>) -> AppResult<()> {
+        self.connect()?.set_text(text.into())?;
+        Ok(())
+    }
+
+    fn connect(&mut self) -> AppResult<&mut arboard::Clipboard> {
+        if self.inner.is_none() {
+            self.inner = Some(arboard::Clipboard::new()?);
+        }
+        Ok(self.inner.as_mut().unwrap())
+    }
+}
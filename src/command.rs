@@ -1,6 +1,12 @@
 use crate::app::{AppAction, AppResult};
-use polars::{df, frame::DataFrame};
-use std::{collections::HashMap, error::Error};
+use crate::args::AppTheme;
+use crate::sql::sql_literal;
+use crate::utils::{is_valid_date_format, split_args, ListStyle};
+use crate::widget::PaletteItem;
+use clap::ValueEnum;
+use polars::{df, frame::DataFrame, prelude::QuoteStyle};
+use ratatui::style::Color;
+use std::{collections::HashMap, error::Error, str::FromStr};
 
 pub type ParseFn = fn(&str) -> Result<AppAction, Box<dyn Error>>;
 pub type CommandRegistery = HashMap<&'static str, ParseFn>;
@@ -44,6 +50,24 @@ impl Default for Commands {
                     "Query the data in Structured Query Language(SQL). The table name is the file name without extension",
                 parser: command_query,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":explain"),
+                usage: ":explain <query>",
+                description: "Open a new tab with the optimized query plan of the SQL query, without running it",
+                parser: command_explain,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":set"),
+                usage: ":set [<name> = <value>]",
+                description: "Set a variable referenced as $name in SQL queries and filters, or list the variables",
+                parser: command_set,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":unset"),
+                usage: ":unset <name>",
+                description: "Remove a variable set with :set",
+                parser: command_unset,
+            },
             CommandEntry {
                 prefix: Prefix::ShortAndLong(":q", ":quit"),
                 usage: ":q",
@@ -74,6 +98,12 @@ impl Default for Commands {
                 description: "Reset the original data frame",
                 parser: command_reset,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":full"),
+                usage: ":full",
+                description: "Load the whole file of a tab opened with --preview",
+                parser: command_load_full,
+            },
             CommandEntry {
                 prefix: Prefix::Long(":help"),
                 usage: ":help",
@@ -86,6 +116,24 @@ impl Default for Commands {
                 description: "Query current data frame for columns/functions",
                 parser: command_select,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":col"),
+                usage: ":col <name_or_prefix>",
+                description: "Scroll to and focus the column with the name, or the only column starting with it",
+                parser: command_goto_column,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":find"),
+                usage: ":find <text>",
+                description: "Jump to the next row whose value in the focused column contains the text",
+                parser: command_find,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":findall"),
+                usage: ":findall <text>",
+                description: "List the cells of every tab containing the text, Enter jumping to the selected one",
+                parser: command_find_all,
+            },
             CommandEntry {
                 prefix: Prefix::ShortAndLong(":F", ":filter"),
                 usage: ":filter <condition(s)>",
@@ -98,12 +146,114 @@ impl Default for Commands {
                 description: "Sort current data frame by column(s)",
                 parser: command_order,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":bar"),
+                usage: ":bar [column]",
+                description: "Toggle a bar beside each value of a numeric column, or the focused column, sized between its min and max",
+                parser: command_toggle_bar,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":width"),
+                usage: ":width (<percent> | auto) [column]",
+                description: "Set the width of a column, or the focused column, as a percentage of the table width, or back to fitting its values",
+                parser: command_column_width,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":datefmt"),
+                usage: ":datefmt (<format> | default) [column]",
+                description: "Show dates and times, of all columns or the given one, in a strftime format such as '%d/%m/%Y %H:%M'",
+                parser: command_date_format,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":highlight"),
+                usage: ":highlight (<column> <op> <value> <color> | reset [column])",
+                description: "Draw the cells of a column satisfying a comparison in a color, or remove the rules",
+                parser: command_highlight,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":rename-col"),
+                usage: ":rename-col <old_name> <new_name>",
+                description: "Rename a column of the current data frame, double quote names with spaces",
+                parser: command_rename_column,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":cast-all"),
+                usage: ":cast-all <data_type>",
+                description: "Cast every column that can be cast without losing values to i64, f64, str, bool, date, etc.",
+                parser: command_cast_all,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":explode"),
+                usage: ":explode [column]",
+                description: "Expand the elements of a list column, or the focused column, into rows",
+                parser: command_explode,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":dropna"),
+                usage: ":dropna [column(s)]",
+                description: "Remove the rows with a null in any column, or in one of the given columns",
+                parser: command_drop_nulls,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":drop"),
+                usage: ":drop [column(s)]",
+                description: "Remove the column(s), or the focused column",
+                parser: command_drop_columns,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":group"),
+                usage: ":group <column(s)> (sum | mean | count | min | max) [column]",
+                description: "Open a new tab aggregating a column for each group of the comma separated column(s)",
+                parser: command_group,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":pivot"),
+                usage: ":pivot index=<column> columns=<column> values=<column> [agg=<aggregation>]",
+                description: "Open a new tab with a row for each index value and a column for each columns value, aggregating the values (first by default)",
+                parser: command_pivot,
+            },
             CommandEntry {
                 prefix: Prefix::Long(":schema"),
                 usage: ":schema",
                 description: "Show loaded data frame(s), their schmea(s), and their path(s)",
                 parser: command_tables,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":errors"),
+                usage: ":errors",
+                description: "Show the recent errors, most recent first",
+                parser: command_errors,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":meta"),
+                usage: ":meta",
+                description: "Show the compression codec and size of each column of the current Parquet file",
+                parser: command_metadata,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":sample"),
+                usage: ":sample <rows> [seed]",
+                description: "Replace current data frame with randomly sampled rows, optionally seeded for reproducibility",
+                parser: command_sample,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":reverse"),
+                usage: ":reverse",
+                description: "Reverse the order of the rows, e.g. to see the end of a log first",
+                parser: command_reverse,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":rownum"),
+                usage: ":rownum [name]",
+                description: "Append a column numbering the rows from 1, in their current order",
+                parser: command_row_number,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":rank"),
+                usage: ":rank <column> [asc | desc] [name]",
+                description: "Append a column ranking the values of a column, ties sharing the lowest rank",
+                parser: command_rank,
+            },
             CommandEntry {
                 prefix: Prefix::Long(":rand"),
                 usage: ":rand",
@@ -116,12 +266,30 @@ impl Default for Commands {
                 description: "Change tabular's view to table or sheet",
                 parser: command_change_view,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":open"),
+                usage: ":open <path> [options]",
+                description: "Open a file in a new tab, read with the options given on the command line or after the path",
+                parser: command_open,
+            },
             CommandEntry {
                 prefix: Prefix::Long(":tabn"),
                 usage: ":tabn <query>",
                 description: "Create a new tab with the query",
                 parser: command_new_tab,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":join"),
+                usage: ":join <table> <table> ON <key>[=<key>] [inner | left | right | outer]",
+                description: "Create a new tab joining two tables on their key columns, with an inner join by default",
+                parser: command_join,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":concat"),
+                usage: ":concat [table(s)]",
+                description: "Create a new tab stacking the tables (all open ones if none are given) with the same schema",
+                parser: command_concat,
+            },
             CommandEntry {
                 prefix: Prefix::Long(":tabr"),
                 usage: ":tabr <tab_index>",
@@ -134,6 +302,36 @@ impl Default for Commands {
                 description: "Select the tab at the index",
                 parser: command_select_tab,
             },
+            CommandEntry {
+                prefix: Prefix::Long(":copycol"),
+                usage: ":copycol [lines | comma] [force]",
+                description: "Copy the values of the focused column to the clipboard",
+                parser: command_copy_column,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":export"),
+                usage: ":export <path> [--delimiter <char>] [--quote (necessary | always | non-numeric | never)]",
+                description: "Write the current data frame to a CSV file, comma separated and quoted when necessary by default",
+                parser: command_export,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":copytsv"),
+                usage: ":copytsv [force]",
+                description: "Copy the visible columns of the bookmarked rows, or of every row, as tab separated values with headers",
+                parser: command_copy_view,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":theme"),
+                usage: ":theme (monokai | argonaut | terminal | reset)",
+                description: "Change the theme of the current tab, or reset it to the global theme",
+                parser: command_tab_theme,
+            },
+            CommandEntry {
+                prefix: Prefix::Long(":macro"),
+                usage: ":macro (record <name> | stop | run <name> | list)",
+                description: "Record the keys typed until ':macro stop' as a macro, run one, or list them",
+                parser: command_macro,
+            },
         ])
     }
 }
@@ -154,6 +352,19 @@ impl Commands {
             .collect()
     }
 
+    pub fn into_palette_items(self) -> Vec<PaletteItem> {
+        self.0
+            .into_iter()
+            .filter_map(|cmd| {
+                Some(PaletteItem {
+                    name: cmd.prefix.long()?,
+                    usage: cmd.usage,
+                    description: cmd.description,
+                })
+            })
+            .collect()
+    }
+
     pub fn into_data_frame(self) -> DataFrame {
         let len = self.0.len();
         let (short, long, usage, description) = self.0.into_iter().fold(
@@ -185,6 +396,39 @@ fn command_query(query: &str) -> AppResult<AppAction> {
     Ok(AppAction::SqlQuery(query.to_owned()))
 }
 
+fn command_set(query: &str) -> AppResult<AppAction> {
+    if query.trim().is_empty() {
+        return Ok(AppAction::SqlVariables);
+    }
+    let (name, value) = query
+        .split_once('=')
+        .map(|(name, value)| (name.trim().trim_start_matches('$'), value.trim()))
+        .filter(|(name, value)| is_variable_name(name) && !value.is_empty())
+        .ok_or("Usage: :set <name> = <value>")?;
+    Ok(AppAction::SqlSetVariable(
+        name.to_owned(),
+        sql_literal(value)?,
+    ))
+}
+
+fn command_unset(query: &str) -> AppResult<AppAction> {
+    match query.trim().trim_start_matches('$') {
+        name if is_variable_name(name) => Ok(AppAction::SqlUnsetVariable(name.to_owned())),
+        _ => Err("Usage: :unset <name>".into()),
+    }
+}
+
+/// Whether the name can be referenced as `$name`: a letter or underscore
+/// followed by letters, digits, and underscores.
+fn is_variable_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn command_explain(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::SqlExplain(query.to_owned()))
+}
+
 fn command_quit(_query: &str) -> AppResult<AppAction> {
     Ok(AppAction::Quit)
 }
@@ -195,6 +439,27 @@ fn command_goto(line: &str) -> AppResult<AppAction> {
     ))
 }
 
+fn command_goto_column(name: &str) -> AppResult<AppAction> {
+    match name.trim() {
+        "" => Err("Column name is required".into()),
+        name => Ok(AppAction::TabularGotoColumn(name.to_owned())),
+    }
+}
+
+fn command_find(text: &str) -> AppResult<AppAction> {
+    match text.trim() {
+        "" => Err("Text to find is required".into()),
+        text => Ok(AppAction::TabularFind(text.to_owned())),
+    }
+}
+
+fn command_find_all(text: &str) -> AppResult<AppAction> {
+    match text.trim() {
+        "" => Err("Text to find is required".into()),
+        text => Ok(AppAction::TabSearch(text.to_owned())),
+    }
+}
+
 fn command_select_up(lines: &str) -> AppResult<AppAction> {
     Ok(match lines {
         "page" => AppAction::TabularGoUpFullPage,
@@ -215,6 +480,10 @@ fn command_reset(_: &str) -> AppResult<AppAction> {
     Ok(AppAction::TabularReset)
 }
 
+fn command_load_full(_: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularLoadFull)
+}
+
 fn command_help(_: &str) -> AppResult<AppAction> {
     Ok(AppAction::Help)
 }
@@ -231,10 +500,175 @@ fn command_order(query: &str) -> AppResult<AppAction> {
     Ok(AppAction::TabularOrder(query.to_owned()))
 }
 
+fn command_toggle_bar(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularToggleBar(query.trim().to_owned()))
+}
+
+fn command_column_width(query: &str) -> AppResult<AppAction> {
+    let (percent, column) = match split_args(query).as_slice() {
+        [percent] => (percent.to_owned(), String::new()),
+        [percent, column] => (percent.to_owned(), column.to_owned()),
+        _ => Err("Usage: :width (<percent> | auto) [column]")?,
+    };
+    let percent = match percent.as_str() {
+        "auto" => None,
+        percent => Some(
+            percent
+                .trim_end_matches('%')
+                .parse()
+                .map_err(|_| format!("Invalid percentage '{}'", percent))?,
+        ),
+    };
+    Ok(AppAction::TabularColumnWidth(column, percent))
+}
+
+fn command_date_format(query: &str) -> AppResult<AppAction> {
+    let (format, column) = match split_args(query).as_slice() {
+        [format] => (format.to_owned(), None),
+        [format, column] => (format.to_owned(), Some(column.to_owned())),
+        _ => Err("Usage: :datefmt (<format> | default) [column]")?,
+    };
+    let format = match format.as_str() {
+        "default" => None,
+        format if is_valid_date_format(format) => Some(format.to_owned()),
+        format => Err(format!("Invalid date format '{}'", format))?,
+    };
+    Ok(AppAction::TabularDateFormat(format, column))
+}
+
+fn command_macro(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [record, name] if record == "record" => Ok(AppAction::MacroRecord(name.to_owned())),
+        [stop] if stop == "stop" => Ok(AppAction::MacroStop),
+        [run, name] if run == "run" => Ok(AppAction::MacroRun(name.to_owned())),
+        [list] if list == "list" => Ok(AppAction::MacroList),
+        _ => Err("Usage: :macro (record <name> | stop | run <name> | list)".into()),
+    }
+}
+
+fn command_highlight(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [reset] if reset == "reset" => Ok(AppAction::TabularResetHighlights(None)),
+        [reset, column] if reset == "reset" => {
+            Ok(AppAction::TabularResetHighlights(Some(column.to_owned())))
+        }
+        [column, op, value, color] => Ok(AppAction::TabularHighlight(
+            column.to_owned(),
+            op.to_owned(),
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+                .unwrap_or(value)
+                .to_owned(),
+            Color::from_str(color).map_err(|_| format!("Invalid color '{}'", color))?,
+        )),
+        _ => Err(
+            "Usage: :highlight <column> <op> <value> <color> or :highlight reset [column]".into(),
+        ),
+    }
+}
+
+fn command_rename_column(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [old, new] if !new.is_empty() => Ok(AppAction::TabularRenameColumn(
+            old.to_owned(),
+            new.to_owned(),
+        )),
+        _ => Err("Usage: :rename-col <old_name> <new_name>".into()),
+    }
+}
+
+fn command_join(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [left, right, on, keys, how @ ..] if on.eq_ignore_ascii_case("on") && how.len() < 2 => {
+            let (left_key, right_key) = keys.split_once('=').unwrap_or((keys, keys));
+            Ok(AppAction::TabJoin(
+                left.to_owned(),
+                right.to_owned(),
+                left_key.to_owned(),
+                right_key.to_owned(),
+                how.first()
+                    .map(|how| how.to_lowercase())
+                    .unwrap_or("inner".to_owned()),
+            ))
+        }
+        _ => Err(
+            "Usage: :join <table> <table> ON <key>[=<key>] [inner | left | right | outer]".into(),
+        ),
+    }
+}
+
+fn command_concat(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabConcat(split_args(query)))
+}
+
+fn command_cast_all(query: &str) -> AppResult<AppAction> {
+    match query.trim() {
+        "" => Err("Data type is required".into()),
+        dtype => Ok(AppAction::TabularCastAll(dtype.to_owned())),
+    }
+}
+
+fn command_explode(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularExplode(query.trim().to_owned()))
+}
+
+fn command_drop_nulls(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularDropNulls(split_args(query)))
+}
+
+fn command_drop_columns(query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularDropColumns(split_args(query)))
+}
+
+fn command_group(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [by, agg, rest @ ..] if rest.len() <= 1 => Ok(AppAction::TabularGroup(
+            by.split(',').map(|name| name.trim().to_owned()).collect(),
+            agg.to_lowercase(),
+            rest.first().cloned(),
+        )),
+        _ => Err("Usage: :group <column(s)> <aggregation> [column]".into()),
+    }
+}
+
+fn command_pivot(query: &str) -> AppResult<AppAction> {
+    let mut args = HashMap::new();
+    for arg in split_args(query) {
+        match arg.split_once('=') {
+            Some((key @ ("index" | "columns" | "values" | "agg"), value)) if !value.is_empty() => {
+                args.insert(key.to_owned(), value.to_owned());
+            }
+            _ => Err(format!("Invalid argument '{}'", arg))?,
+        }
+    }
+    let mut arg = |key: &str| {
+        args.remove(key).ok_or(
+            "Usage: :pivot index=<column> columns=<column> values=<column> [agg=<aggregation>]",
+        )
+    };
+    Ok(AppAction::TabularPivot(
+        arg("index")?,
+        arg("columns")?,
+        arg("values")?,
+        arg("agg")
+            .map(|agg| agg.to_lowercase())
+            .unwrap_or("first".to_owned()),
+    ))
+}
+
 fn command_tables(_query: &str) -> AppResult<AppAction> {
     Ok(AppAction::SqlSchema)
 }
 
+fn command_errors(_query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::ErrorLog)
+}
+
+fn command_metadata(_query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::SqlMetadata)
+}
+
 fn command_change_view(query: &str) -> AppResult<AppAction> {
     Ok(match query {
         "table" => AppAction::TabularTableView,
@@ -244,10 +678,57 @@ fn command_change_view(query: &str) -> AppResult<AppAction> {
     })
 }
 
+fn command_sample(query: &str) -> AppResult<AppAction> {
+    let mut args = query.split_whitespace();
+    let rows = args.next().ok_or("Number of rows is required")?.parse()?;
+    let seed = args.next().map(str::parse).transpose()?;
+    if args.next().is_some() {
+        Err("Usage: :sample <rows> [seed]")?
+    }
+    Ok(AppAction::TabularSample(rows, seed))
+}
+
+fn command_reverse(_query: &str) -> AppResult<AppAction> {
+    Ok(AppAction::TabularReverse)
+}
+
+fn command_row_number(query: &str) -> AppResult<AppAction> {
+    match split_args(query).as_slice() {
+        [] => Ok(AppAction::TabularRowNumber("row_number".to_owned())),
+        [name] => Ok(AppAction::TabularRowNumber(name.to_owned())),
+        _ => Err("Usage: :rownum [name]".into()),
+    }
+}
+
+fn command_rank(query: &str) -> AppResult<AppAction> {
+    let args = split_args(query);
+    let (column, order, name) = match args.as_slice() {
+        [column] => (column, "asc", None),
+        [column, order] => (column, order.as_str(), None),
+        [column, order, name] => (column, order.as_str(), Some(name)),
+        _ => Err("Usage: :rank <column> [asc | desc] [name]")?,
+    };
+    let descending = match order {
+        "asc" => false,
+        "desc" => true,
+        order => Err(format!("Invalid order '{}', expected asc or desc", order))?,
+    };
+    let name = name.cloned().unwrap_or_else(|| format!("{}_rank", column));
+    Ok(AppAction::TabularRank(column.to_owned(), descending, name))
+}
+
 fn command_select_random_row(_query: &str) -> AppResult<AppAction> {
     Ok(AppAction::TabularGotoRandom)
 }
 
+fn command_open(query: &str) -> AppResult<AppAction> {
+    let args = split_args(query);
+    let (path, options) = args
+        .split_first()
+        .ok_or("Usage: :open <path> [options]")?;
+    Ok(AppAction::TabOpen(path.into(), options.to_vec()))
+}
+
 fn command_new_tab(query: &str) -> AppResult<AppAction> {
     Ok(AppAction::TabNew(query.to_owned()))
 
@@ -259,4 +740,71 @@ fn command_remove_tab(query: &str) -> AppResult<AppAction> {
 
 fn command_select_tab(query: &str) -> AppResult<AppAction> {
     Ok(AppAction::TabSelect(query.parse()?))
-}
\ No newline at end of file
+}
+
+fn command_tab_theme(query: &str) -> AppResult<AppAction> {
+    Ok(match query.trim() {
+        "" | "reset" => AppAction::TabTheme(None),
+        name => AppAction::TabTheme(Some(
+            AppTheme::from_str(name, true).map_err(|_| format!("Invalid theme '{}'", name))?,
+        )),
+    })
+}
+
+fn command_copy_column(query: &str) -> AppResult<AppAction> {
+    let mut style = ListStyle::Lines;
+    let mut force = false;
+    for arg in query.split_whitespace() {
+        match arg {
+            "lines" => style = ListStyle::Lines,
+            "comma" => style = ListStyle::Comma,
+            "force" => force = true,
+            _ => Err(format!("Invalid argument '{}'", arg))?,
+        }
+    }
+    Ok(AppAction::TabularCopyColumn(style, force))
+}
+
+fn command_export(query: &str) -> AppResult<AppAction> {
+    let args = split_args(query);
+    let usage = || "Usage: :export <path> [--delimiter <char>] [--quote <style>]";
+    let (path, mut options) = args.split_first().ok_or_else(usage)?;
+    let mut separator = b',';
+    let mut quote_style = QuoteStyle::Necessary;
+    while let Some((option, rest)) = options.split_first() {
+        let (value, rest) = rest.split_first().ok_or_else(usage)?;
+        match option.as_str() {
+            "--delimiter" => {
+                separator = match value.as_str() {
+                    "\\t" | "tab" => b'\t',
+                    value if value.len() == 1 && value.is_ascii() => value.as_bytes()[0],
+                    _ => Err(format!("Invalid delimiter '{}'", value))?,
+                }
+            }
+            "--quote" => {
+                quote_style = match value.as_str() {
+                    "necessary" => QuoteStyle::Necessary,
+                    "always" => QuoteStyle::Always,
+                    "non-numeric" => QuoteStyle::NonNumeric,
+                    "never" => QuoteStyle::Never,
+                    _ => Err(format!("Invalid quote style '{}'", value))?,
+                }
+            }
+            _ => Err(format!("Invalid option '{}'", option))?,
+        }
+        options = rest;
+    }
+    Ok(AppAction::TabularExport(
+        path.into(),
+        separator,
+        quote_style,
+    ))
+}
+
+fn command_copy_view(query: &str) -> AppResult<AppAction> {
+    match query.trim() {
+        "" => Ok(AppAction::TabularCopyView(false)),
+        "force" => Ok(AppAction::TabularCopyView(true)),
+        arg => Err(format!("Invalid argument '{}'", arg).into()),
+    }
+}
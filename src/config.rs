@@ -0,0 +1,252 @@
+use std::{collections::BTreeMap, ffi::OsString, fs, path::Path};
+
+use clap::CommandFactory;
+use crossterm::event::{KeyCode, KeyModifiers};
+use serde::Deserialize;
+
+use crate::{app::AppResult, args::Args, command::Commands};
+
+/// Settings read from a TOML file, each named after the long form of a
+/// command line option, e.g. `theme = "argonaut"` or `watch = true`, and key
+/// bindings of the table view to commands in a `[keybinds]` table, e.g.
+/// `"ctrl+g" = ":goto 1"`.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Config {
+    args: Vec<OsString>,
+    keybinds: Vec<(KeyCode, KeyModifiers, String)>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    keybinds: BTreeMap<String, String>,
+    #[serde(flatten)]
+    settings: BTreeMap<String, toml::Value>,
+}
+
+impl Config {
+    pub fn read(path: impl AsRef<Path>) -> AppResult<Self> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read config {}: {}", path.display(), err))?;
+        Ok(Config::parse(text.trim_start_matches('\u{feff}'))
+            .map_err(|err| format!("Invalid config {}: {}", path.display(), err))?)
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let file = toml::from_str::<ConfigFile>(text).map_err(|err| err.to_string())?;
+
+        let command = Args::command();
+        let mut args = Vec::new();
+        let mut keys = Vec::<String>::new();
+        for (key, value) in file.settings {
+            let key = key.replace('_', "-");
+            let arg = command
+                .get_arguments()
+                .find(|arg| arg.get_long() == Some(key.as_str()) && key != "config")
+                .ok_or_else(|| format!("unknown setting '{}'", key))?;
+            if keys.contains(&key) {
+                Err(format!("'{}' is set more than once", key))?
+            }
+            let flag = arg
+                .get_num_args()
+                .is_some_and(|range| range.min_values() == 0);
+            let value = match (flag, value) {
+                (true, toml::Value::Boolean(value)) => value.to_string(),
+                (true, _) => Err(format!("'{}' expects true or false", key))?,
+                (false, toml::Value::String(value)) => value,
+                (false, toml::Value::Integer(value)) => value.to_string(),
+                (false, toml::Value::Float(value)) => value.to_string(),
+                (false, _) => Err(format!("'{}' expects a string or a number", key))?,
+            };
+            args.push(format!("--{}={}", key, value).into());
+            keys.push(key);
+        }
+
+        let exec = Commands::default().into_exec();
+        let mut keybinds = Vec::new();
+        for (key, command) in file.keybinds {
+            let (code, modifiers) = parse_key(&key).map_err(|err| format!("keybinds: {}", err))?;
+            let name = command
+                .split_once(' ')
+                .map_or(command.as_str(), |(name, _)| name);
+            if !exec.contains_key(name) {
+                Err(format!(
+                    "keybinds: unknown command '{}' bound to '{}'",
+                    name, key
+                ))?
+            }
+            if keybinds
+                .iter()
+                .any(|(other, other_modifiers, _)| (other, other_modifiers) == (&code, &modifiers))
+            {
+                Err(format!("keybinds: '{}' is bound more than once", key))?
+            }
+            keybinds.push((code, modifiers, command));
+        }
+        Ok(Config { args, keybinds })
+    }
+
+    /// Command line arguments equivalent to the settings, meant to precede
+    /// the actual ones so that those take precedence, e.g. `--watch=false`
+    /// over `watch = true`.
+    pub fn args(&self) -> &[OsString] {
+        &self.args
+    }
+
+    /// Keys bound to command lines in the table view.
+    pub fn keybinds(&self) -> &[(KeyCode, KeyModifiers, String)] {
+        &self.keybinds
+    }
+}
+
+/// Parses keys such as `J`, `ctrl+d`, `alt+enter`, or `f5`.
+fn parse_key(key: &str) -> Result<(KeyCode, KeyModifiers), String> {
+    let (prefix, name) = match key.rsplit_once('+') {
+        Some((prefix, name)) if !name.is_empty() => (prefix, name),
+        _ => ("", key),
+    };
+    let mut modifiers = KeyModifiers::empty();
+    for modifier in prefix.split('+').filter(|modifier| !modifier.is_empty()) {
+        modifiers |= match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => KeyModifiers::CONTROL,
+            "alt" => KeyModifiers::ALT,
+            "shift" => KeyModifiers::SHIFT,
+            _ => Err(format!("unknown modifier '{}' in '{}'", modifier, key))?,
+        };
+    }
+    let mut chars = name.chars();
+    let code = match (chars.next(), chars.next()) {
+        (Some(c), None) if modifiers.contains(KeyModifiers::SHIFT) => {
+            KeyCode::Char(c.to_ascii_uppercase())
+        }
+        (Some(c), None) => {
+            if c.is_uppercase() {
+                modifiers |= KeyModifiers::SHIFT;
+            }
+            KeyCode::Char(c)
+        }
+        _ => match name.to_lowercase().as_str() {
+            "space" => KeyCode::Char(' '),
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backspace" => KeyCode::Backspace,
+            "delete" => KeyCode::Delete,
+            "insert" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            lower => match lower.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => Err(format!("unknown key '{}'", key))?,
+            },
+        },
+    };
+    Ok((code, modifiers))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "# Tabiew\ntheme = \"argonaut\" # dark\n\nscrolloff = 4\nwatch = true\nno_confirm = false\nseparator = '#'\ninitial-sql = \"SELECT \\\"a\\\"\"\n\n[keybinds]\n\"ctrl+g\" = \":goto 1\"\nJ = \":Q SELECT * FROM a\"\n\"shift+k\" = \":help\"\nf5 = \":reset\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config.args(),
+            [
+                "--initial-sql=SELECT \"a\"",
+                "--no-confirm=false",
+                "--scrolloff=4",
+                "--separator=#",
+                "--theme=argonaut",
+                "--watch=true",
+            ]
+            .map(OsString::from)
+        );
+        assert_eq!(
+            config.keybinds(),
+            [
+                (
+                    KeyCode::Char('J'),
+                    KeyModifiers::SHIFT,
+                    ":Q SELECT * FROM a".to_owned()
+                ),
+                (
+                    KeyCode::Char('g'),
+                    KeyModifiers::CONTROL,
+                    ":goto 1".to_owned()
+                ),
+                (KeyCode::F(5), KeyModifiers::empty(), ":reset".to_owned()),
+                (KeyCode::Char('K'), KeyModifiers::SHIFT, ":help".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_config_overridden_by_cli() {
+        use clap::Parser;
+
+        let config = Config::parse("watch = true\nscrolloff = 4\ntheme = \"argonaut\"").unwrap();
+        let args = Args::parse_from(
+            ["tw".into()]
+                .into_iter()
+                .chain(config.args().iter().cloned())
+                .chain(["--watch=false".into(), "--scrolloff".into(), "1".into()]),
+        );
+        assert!(!args.watch);
+        assert_eq!(args.scrolloff, 1);
+        assert_eq!(args.theme, crate::args::AppTheme::Argonaut);
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        assert_eq!(
+            Config::parse("theme = \"argonaut\"\ncolor = \"red\"").unwrap_err(),
+            "unknown setting 'color'"
+        );
+        assert_eq!(
+            Config::parse("watch = \"yes\"").unwrap_err(),
+            "'watch' expects true or false"
+        );
+        assert_eq!(
+            Config::parse("theme = true").unwrap_err(),
+            "'theme' expects a string or a number"
+        );
+        assert_eq!(
+            Config::parse("no-confirm = true\nno_confirm = true").unwrap_err(),
+            "'no-confirm' is set more than once"
+        );
+        assert_eq!(
+            Config::parse("config = 'other.toml'").unwrap_err(),
+            "unknown setting 'config'"
+        );
+        assert!(Config::parse("theme = \"argonaut").is_err());
+        assert!(Config::parse("theme = argonaut").is_err());
+        assert_eq!(
+            Config::parse("[keybinds]\n\"hyper+x\" = \":help\"").unwrap_err(),
+            "keybinds: unknown modifier 'hyper' in 'hyper+x'"
+        );
+        assert_eq!(
+            Config::parse("[keybinds]\nf13 = \":help\"").unwrap_err(),
+            "keybinds: unknown key 'f13'"
+        );
+        assert_eq!(
+            Config::parse("[keybinds]\nx = \":nope 1\"").unwrap_err(),
+            "keybinds: unknown command ':nope' bound to 'x'"
+        );
+        assert_eq!(
+            Config::parse("[keybinds]\nJ = \":help\"\n\"shift+j\" = \":help\"").unwrap_err(),
+            "keybinds: 'shift+j' is bound more than once"
+        );
+    }
+}
@@ -1,8 +1,13 @@
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 use crate::app::{AppAction, AppState};
+use crate::utils::ListStyle;
+
+/// Time allowed between the keys of a chord.
+const CHORD_TIMEOUT: Duration = Duration::from_secs(1);
 
 #[derive(Debug, PartialEq, Eq, Hash)]
 enum StateKey {
@@ -11,8 +16,13 @@ enum StateKey {
     State(AppState),
 }
 pub type Action = AppAction;
+type Chord = Vec<(KeyCode, KeyModifiers)>;
 pub struct Keybind {
     map: HashMap<StateKey, Action>,
+    chords: Vec<(AppState, Chord, Action)>,
+    pending: Chord,
+    pending_state: AppState,
+    pending_since: Instant,
 }
 
 impl Default for Keybind {
@@ -111,6 +121,162 @@ impl Default for Keybind {
                     AppAction::TabularGoDown(1),
                 ),
 
+                // Move to prev/next column
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Left, KeyModifiers::empty()),
+                    AppAction::TabularGoLeft(1),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Right, KeyModifiers::empty()),
+                    AppAction::TabularGoRight(1),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('h'), KeyModifiers::empty()),
+                    AppAction::TabularGoLeft(1),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('l'), KeyModifiers::empty()),
+                    AppAction::TabularGoRight(1),
+                ),
+
+                // Hide/show columns
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('z'), KeyModifiers::empty()),
+                    AppAction::TabularToggleColumn,
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('Z'), KeyModifiers::SHIFT),
+                    AppAction::TabularShowAllColumns,
+                ),
+
+                // Toggle thousands grouping
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char(','), KeyModifiers::empty()),
+                    AppAction::TabularToggleThousands,
+                ),
+
+                // Cycle boolean rendering
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('b'), KeyModifiers::empty()),
+                    AppAction::TabularCycleBoolGlyphs,
+                ),
+
+                // Cycle the integer base of the focused column
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('x'), KeyModifiers::empty()),
+                    AppAction::TabularCycleIntBase,
+                ),
+
+                // Cycle header truncation
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('w'), KeyModifiers::empty()),
+                    AppAction::TabularCycleHeaderMode,
+                ),
+
+                // Toggle missing value share in the header
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('n'), KeyModifiers::empty()),
+                    AppAction::TabularToggleMissing,
+                ),
+
+                // Toggle source path in the status bar
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('P'), KeyModifiers::SHIFT),
+                    AppAction::StatusBarTogglePath,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('P'), KeyModifiers::SHIFT),
+                    AppAction::StatusBarTogglePath,
+                ),
+
+                // Toggle aggregation footer
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('s'), KeyModifiers::empty()),
+                    AppAction::TabularToggleFooter,
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('S'), KeyModifiers::SHIFT),
+                    AppAction::TabularToggleTotals,
+                ),
+
+                // Toggle records before and after the selected one in sheet view
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('c'), KeyModifiers::empty()),
+                    AppAction::TabularToggleCompare,
+                ),
+
+                // Toggle row number gutter
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('#'), KeyModifiers::empty()),
+                    AppAction::TabularToggleGutter,
+                ),
+
+                // Toggle dense mode
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('D'), KeyModifiers::SHIFT),
+                    AppAction::TabularToggleDense,
+                ),
+
+                // Copy column
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('C'), KeyModifiers::SHIFT),
+                    AppAction::TabularCopyColumn(ListStyle::Lines, false),
+                ),
+
+                // Jump to the selected match of :findall
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Enter, KeyModifiers::empty()),
+                    AppAction::TabGotoMatch,
+                ),
+
+                // Run the last macro
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('@'), KeyModifiers::empty()),
+                    AppAction::MacroRunLast,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('@'), KeyModifiers::empty()),
+                    AppAction::MacroRunLast,
+                ),
+
+                // Copy the column name
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('y'), KeyModifiers::CONTROL),
+                    AppAction::TabularCopyColumnName,
+                ),
+
+                // Copy the table as TSV
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('Y'), KeyModifiers::SHIFT),
+                    AppAction::TabularCopyView(false),
+                ),
+
+                // Bookmarks
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('m'), KeyModifiers::empty()),
+                    AppAction::TabularToggleBookmark,
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char(']'), KeyModifiers::empty()),
+                    AppAction::TabularGotoNextBookmark,
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('['), KeyModifiers::empty()),
+                    AppAction::TabularGotoPrevBookmark,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('m'), KeyModifiers::empty()),
+                    AppAction::TabularToggleBookmark,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char(']'), KeyModifiers::empty()),
+                    AppAction::TabularGotoNextBookmark,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('['), KeyModifiers::empty()),
+                    AppAction::TabularGotoPrevBookmark,
+                ),
+
                 // Move to first/last record
                 (
                     StateKey::Exact(AppState::Sheet, KeyCode::Home, KeyModifiers::empty()),
@@ -128,18 +294,10 @@ impl Default for Keybind {
                     StateKey::Exact(AppState::Table, KeyCode::End, KeyModifiers::empty()),
                     AppAction::TabularGotoLast,
                 ),
-                (
-                    StateKey::Exact(AppState::Sheet, KeyCode::Char('g'), KeyModifiers::empty()),
-                    AppAction::TabularGotoFirst,
-                ),
                 (
                     StateKey::Exact(AppState::Sheet, KeyCode::Char('G'), KeyModifiers::SHIFT),
                     AppAction::TabularGotoLast,
                 ),
-                (
-                    StateKey::Exact(AppState::Table, KeyCode::Char('g'), KeyModifiers::empty()),
-                    AppAction::TabularGotoFirst,
-                ),
                 (
                     StateKey::Exact(AppState::Table, KeyCode::Char('G'), KeyModifiers::SHIFT),
                     AppAction::TabularGotoLast,
@@ -219,6 +377,142 @@ impl Default for Keybind {
                     AppAction::StatusBarCommand("goto 9".to_owned()),
                 ),
 
+                // Jump to tab by number, Alt+0 for the last tab
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('1'), KeyModifiers::ALT),
+                    AppAction::TabSelect(1),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('2'), KeyModifiers::ALT),
+                    AppAction::TabSelect(2),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('3'), KeyModifiers::ALT),
+                    AppAction::TabSelect(3),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('4'), KeyModifiers::ALT),
+                    AppAction::TabSelect(4),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('5'), KeyModifiers::ALT),
+                    AppAction::TabSelect(5),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('6'), KeyModifiers::ALT),
+                    AppAction::TabSelect(6),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('7'), KeyModifiers::ALT),
+                    AppAction::TabSelect(7),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('8'), KeyModifiers::ALT),
+                    AppAction::TabSelect(8),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('9'), KeyModifiers::ALT),
+                    AppAction::TabSelect(9),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('0'), KeyModifiers::ALT),
+                    AppAction::TabSelectLast,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('1'), KeyModifiers::ALT),
+                    AppAction::TabSelect(1),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('2'), KeyModifiers::ALT),
+                    AppAction::TabSelect(2),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('3'), KeyModifiers::ALT),
+                    AppAction::TabSelect(3),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('4'), KeyModifiers::ALT),
+                    AppAction::TabSelect(4),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('5'), KeyModifiers::ALT),
+                    AppAction::TabSelect(5),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('6'), KeyModifiers::ALT),
+                    AppAction::TabSelect(6),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('7'), KeyModifiers::ALT),
+                    AppAction::TabSelect(7),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('8'), KeyModifiers::ALT),
+                    AppAction::TabSelect(8),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('9'), KeyModifiers::ALT),
+                    AppAction::TabSelect(9),
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('0'), KeyModifiers::ALT),
+                    AppAction::TabSelectLast,
+                ),
+
+                // Filter by the focused cell
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('f'), KeyModifiers::empty()),
+                    AppAction::TabularFilterSelectedCell,
+                ),
+
+                // Find in the focused column
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('\\'), KeyModifiers::empty()),
+                    AppAction::StatusBarCommand("find ".to_owned()),
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('>'), KeyModifiers::empty()),
+                    AppAction::TabularFindNext,
+                ),
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('<'), KeyModifiers::empty()),
+                    AppAction::TabularFindPrev,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('>'), KeyModifiers::empty()),
+                    AppAction::TabularFindNext,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('<'), KeyModifiers::empty()),
+                    AppAction::TabularFindPrev,
+                ),
+
+                // Select text of the focused cell
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('e'), KeyModifiers::empty()),
+                    AppAction::TextSelectShow,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('e'), KeyModifiers::empty()),
+                    AppAction::TextSelectShow,
+                ),
+
+                // Copy the focused cell
+                (
+                    StateKey::Exact(AppState::Table, KeyCode::Char('y'), KeyModifiers::empty()),
+                    AppAction::CopyMenuShow,
+                ),
+                (
+                    StateKey::Exact(AppState::Sheet, KeyCode::Char('y'), KeyModifiers::empty()),
+                    AppAction::CopyMenuShow,
+                ),
+
+                // Command palette
+                (
+                    StateKey::KeyCode(KeyCode::Char('p'), KeyModifiers::CONTROL),
+                    AppAction::PaletteShow,
+                ),
+
                 // Select Random
                 (
                     StateKey::Exact(AppState::Table, KeyCode::Char('R'), KeyModifiers::SHIFT),
@@ -227,15 +521,158 @@ impl Default for Keybind {
             ]
             .into_iter()
             .collect(),
+            chords: vec![
+                // Move to first record
+                (AppState::Table, chord("gg"), AppAction::TabularGotoFirst),
+                (AppState::Sheet, chord("gg"), AppAction::TabularGotoFirst),
+
+                // Move prev/next tab
+                (AppState::Table, chord("gt"), AppAction::TabSelectedNext),
+                (AppState::Table, chord("gT"), AppAction::TabSelectedPrev),
+                (AppState::Sheet, chord("gt"), AppAction::TabSelectedNext),
+                (AppState::Sheet, chord("gT"), AppAction::TabSelectedPrev),
+            ],
+            pending: Vec::new(),
+            pending_state: AppState::Empty,
+            pending_since: Instant::now(),
         }
     }
 }
 
 impl Keybind {
-    pub fn get_action(&self, state: AppState, key_event: KeyEvent) -> Option<&Action> {
+    /// Action bound to the key, or to the chord it completes. Keys that start
+    /// a chord are held until the next key, unless more than the chord timeout
+    /// passes or the state changes in between; a key that breaks a chord is
+    /// looked up on its own.
+    pub fn get_action(&mut self, state: AppState, key_event: KeyEvent) -> Option<&Action> {
+        if self.pending_since.elapsed() > CHORD_TIMEOUT || self.pending_state != state {
+            self.pending.clear();
+        }
+        self.pending.push((key_event.code, key_event.modifiers));
+        self.pending_state = state;
+        self.pending_since = Instant::now();
+        let pending = std::mem::take(&mut self.pending);
+        let mut chords = self
+            .chords
+            .iter()
+            .filter(|(chord_state, keys, _)| *chord_state == state && keys.starts_with(&pending));
+        match chords.next() {
+            Some((_, keys, action)) if keys.len() == pending.len() => return Some(action),
+            Some(_) => {
+                self.pending = pending;
+                return None;
+            }
+            None => (),
+        }
         self.map
             .get(&StateKey::Exact(state, key_event.code, key_event.modifiers))
             .or(self.map.get(&StateKey::KeyCode(key_event.code, key_event.modifiers)))
             .or(self.map.get(&StateKey::State(state)))
     }
+
+    /// Drops the keys of the chord in progress, for keys handled without the
+    /// bindings, e.g. `:` opening the command line.
+    pub fn clear_pending(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Binds the key to the action in the state, over its default binding
+    /// and the chords it starts.
+    pub fn bind(
+        &mut self,
+        state: AppState,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+        action: Action,
+    ) {
+        self.chords.retain(|(chord_state, keys, _)| {
+            *chord_state != state || keys.first() != Some(&(code, modifiers))
+        });
+        self.map
+            .insert(StateKey::Exact(state, code, modifiers), action);
+    }
+}
+
+/// Keys of a chord typed as the characters, e.g. `gT`.
+fn chord(keys: &str) -> Chord {
+    keys.chars()
+        .map(|c| {
+            let modifiers = if c.is_uppercase() {
+                KeyModifiers::SHIFT
+            } else {
+                KeyModifiers::empty()
+            };
+            (KeyCode::Char(c), modifiers)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(keybind: &mut Keybind, c: char) -> Option<Action> {
+        let modifiers = if c.is_uppercase() {
+            KeyModifiers::SHIFT
+        } else {
+            KeyModifiers::empty()
+        };
+        keybind
+            .get_action(AppState::Table, KeyEvent::new(KeyCode::Char(c), modifiers))
+            .cloned()
+    }
+
+    #[test]
+    fn test_chords() {
+        let mut keybind = Keybind::default();
+        assert_eq!(press(&mut keybind, 'g'), None);
+        assert_eq!(press(&mut keybind, 'g'), Some(AppAction::TabularGotoFirst));
+        assert_eq!(press(&mut keybind, 'g'), None);
+        assert_eq!(press(&mut keybind, 'T'), Some(AppAction::TabSelectedPrev));
+        assert_eq!(press(&mut keybind, 'j'), Some(AppAction::TabularGoDown(1)));
+    }
+
+    #[test]
+    fn test_broken_chord() {
+        let mut keybind = Keybind::default();
+        assert_eq!(press(&mut keybind, 'g'), None);
+        assert_eq!(press(&mut keybind, 'j'), Some(AppAction::TabularGoDown(1)));
+        assert_eq!(press(&mut keybind, 'g'), None);
+        keybind.pending_since -= CHORD_TIMEOUT * 2;
+        assert_eq!(press(&mut keybind, 'g'), None);
+        assert_eq!(press(&mut keybind, 'g'), Some(AppAction::TabularGotoFirst));
+    }
+
+    #[test]
+    fn test_chord_cleared() {
+        let mut keybind = Keybind::default();
+        assert_eq!(press(&mut keybind, 'g'), None);
+        keybind.clear_pending();
+        assert_eq!(press(&mut keybind, 'g'), None);
+        let key = KeyEvent::new(KeyCode::Char('g'), KeyModifiers::empty());
+        assert_eq!(keybind.get_action(AppState::Sheet, key), None);
+        assert_eq!(press(&mut keybind, 'g'), None);
+        assert_eq!(press(&mut keybind, 'g'), Some(AppAction::TabularGotoFirst));
+    }
+
+    #[test]
+    fn test_bind() {
+        let mut keybind = Keybind::default();
+        let action = AppAction::CommandRun(":goto 1".to_owned());
+        keybind.bind(
+            AppState::Table,
+            KeyCode::Char('g'),
+            KeyModifiers::empty(),
+            action.clone(),
+        );
+        keybind.bind(
+            AppState::Table,
+            KeyCode::Char('j'),
+            KeyModifiers::empty(),
+            action.clone(),
+        );
+        assert_eq!(press(&mut keybind, 'g'), Some(action.clone()));
+        assert_eq!(press(&mut keybind, 'j'), Some(action));
+        assert_eq!(press(&mut keybind, 'k'), Some(AppAction::TabularGoUp(1)));
+    }
 }
@@ -19,6 +19,9 @@ pub mod command;
 /// CLI arguments
 pub mod args;
 
+/// Configuration file
+pub mod config;
+
 /// Custom Widgets
 pub mod widget;
 
@@ -26,4 +29,10 @@ pub mod widget;
 pub mod sql;
 
 /// Key Bindings
-pub mod keybind;
\ No newline at end of file
+pub mod keybind;
+
+/// Clipboard
+pub mod clipboard;
+
+/// File readers
+pub mod reader;
@@ -1,76 +1,206 @@
 use clap::Parser;
-use polars::frame::DataFrame;
-use polars::io::csv::read::{CsvParseOptions, CsvReadOptions};
-use polars::io::parquet::read::ParquetReader;
-use polars::io::SerReader;
 use ratatui::backend::CrosstermBackend;
-use std::error::Error;
-use std::fs::File;
+use ratatui::{TerminalOptions, Viewport};
+use std::fs;
 use std::io::{self};
-use std::path::PathBuf;
 use tabiew::app::status_bar::StatusBar;
 use tabiew::app::tabular::{Tabular, TabularType};
-use tabiew::app::{App, AppResult};
-use tabiew::args::{AppTheme, Args, Format, InferSchema};
+use tabiew::app::{App, AppAction, AppResult, AppState, Tabs};
+use tabiew::args::{AppTheme, Args, Format};
 use tabiew::command::Commands;
+use tabiew::config::Config;
 use tabiew::event::{Event, EventHandler};
 use tabiew::keybind::Keybind;
+use tabiew::reader::{
+    exceeds_size, read_parquet_schema, read_schema, resolve_path, s3_download_dir,
+    sheet_table_name, table_name, ReadOptions,
+};
 use tabiew::sql::SqlBackend;
 use tabiew::theme::{Argonaut, Monokai, Terminal};
 use tabiew::tui::Tui;
-use tabiew::utils::{as_ascii, infer_schema_safe};
+use tabiew::utils::is_valid_date_format;
 
 fn main() -> AppResult<()> {
     // Parse CLI
     let args = Args::parse();
+    let (args, config) = match &args.config {
+        Some(path) => {
+            let config = match Config::read(path) {
+                Ok(config) => config,
+                Err(err) => panic!("{}", err),
+            };
+            let mut cli = std::env::args_os();
+            let args = Args::parse_from(
+                cli.next()
+                    .into_iter()
+                    .chain(config.args().iter().cloned())
+                    .chain(cli),
+            );
+            (args, config)
+        }
+        None => (args, Config::default()),
+    };
 
     // Create the sql backend.
     let mut sql_backend = SqlBackend::new();
 
     // Instantiate app components
-    let tabs = args
+    let mut read_options = ReadOptions::from(&args);
+    if let Some(path) = &args.schema {
+        read_options.schema = match read_schema(path) {
+            Ok(schema) => schema,
+            Err(err) => panic!("{}", err),
+        };
+    }
+    if let Some(format) = args.date_format.as_ref() {
+        if !is_valid_date_format(format) {
+            panic!("Invalid date format '{}'", format);
+        }
+    }
+    if args.schema_only && !matches!(args.format, Format::Parquet) {
+        panic!("--schema-only is only supported for Parquet files");
+    }
+    let mut limited = Vec::new();
+    let mut skipped = Vec::new();
+    let mut peeked = Vec::new();
+    let mut converted = Vec::new();
+    let stdin_tab = args.stdin.then(|| {
+        let df = match read_options.read_stdin() {
+            Ok(df) => df,
+            Err(err) => panic!("{}", err),
+        };
+        let name = sql_backend.register("stdin", df.clone(), "stdin".into());
+        Tabular::new(df, TabularType::Name(name))
+    });
+    let file_tabs = args
         .files
         .iter()
-        .map(|path| {
-            let name = path
-                .file_stem()
-                .expect("Invalid file name")
-                .to_string_lossy()
-                .into_owned();
+        .flat_map(|path| {
+            let path = &match resolve_path(path) {
+                Ok(path) => path,
+                Err(err) => panic!("{}", err),
+            };
+            let read_options = read_options.for_path(path);
 
-            let df = match args.format {
-                Format::Dsv => match read_csv(
-                    path.clone(),
-                    &args.infer_schema,
-                    args.quote_char,
-                    args.separator,
-                    args.no_header,
-                    args.ignore_errors,
-                ) {
-                    Ok(df) => df,
-                    Err(err) => panic!("{}", err),
-                },
-                Format::Parquet => match read_parquet(path.clone()) {
-                    Ok(df) => df,
+            if args.schema_only {
+                let name = table_name(path).expect("Invalid file name");
+                let (df, rows) = match read_parquet_schema(path) {
+                    Ok(schema) => schema,
                     Err(err) => panic!("{}", err),
-                },
+                };
+                let name = sql_backend.register(&name, df.clone(), path.clone());
+                peeked.push(format!("{} ({} rows)", name, rows));
+                return vec![Tabular::new(df, TabularType::Name(name))
+                    .with_preview(true)
+                    .with_read_options(read_options)];
+            }
+
+            let over_limit = match args.limit_memory.map(|limit| exceeds_size(path, limit)) {
+                Some(Ok(over_limit)) => over_limit,
+                Some(Err(err)) => panic!("{}", err),
+                None => false,
+            };
+            if over_limit && !read_options.can_preview(path) {
+                skipped.push(path.display().to_string());
+                return Vec::new();
+            }
+            let preview = (args.preview || over_limit) && read_options.can_preview(path);
+            let sheets = match read_options.sheets(path) {
+                Ok(sheets) => sheets,
+                Err(err) => panic!("{}", err),
             };
-            let name = sql_backend.register(&name, df.clone(), path.clone());
-            Tabular::new(df, TabularType::Name(name))
+            let count = sheets.len();
+            sheets
+                .into_iter()
+                .map(|read_options| {
+                    let name = sheet_table_name(path, read_options.sheet.as_deref(), count)
+                        .expect("Invalid file name");
+                    let (df, columns) = match read_options.read_reporting(path, preview) {
+                        Ok(read) => read,
+                        Err(err) => panic!("{}", err),
+                    };
+                    let name = sql_backend.register(&name, df.clone(), path.clone());
+                    if over_limit {
+                        limited.push(name.clone());
+                    }
+                    converted.extend(columns.iter().map(|column| format!("{}.{}", name, column)));
+                    Tabular::new(df, TabularType::Name(name))
+                        .with_preview(preview)
+                        .with_read_options(read_options)
+                })
+                .collect()
         })
-        .collect();
-    let status_bar = StatusBar::default();
+        .collect::<Vec<_>>();
+    let mut status_bar = StatusBar::default();
+    if !skipped.is_empty() {
+        status_bar.show_error(format!(
+            "Not loaded over the memory limit since they can not be previewed: {} (use :open to load anyway)",
+            skipped.join(", ")
+        ))?;
+    } else if !peeked.is_empty() {
+        status_bar.show_message(format!(
+            "Schema of {} (use :full to load)",
+            peeked.join(", ")
+        ))?;
+    } else if !limited.is_empty() {
+        status_bar.show_message(format!(
+            "Over the memory limit, previewing {} (use :full to load)",
+            limited.join(", ")
+        ))?;
+    } else if !converted.is_empty() {
+        status_bar.show_message(format!(
+            "Parsed thousands separators in {}",
+            converted.join(", ")
+        ))?;
+    }
+    let initial_tab = args
+        .initial_sql
+        .as_ref()
+        .and_then(|query| match sql_backend.execute(query) {
+            Ok(df) => Some(Tabular::new(df, TabularType::Query(query.to_owned()))),
+            Err(err) => {
+                status_bar.show_error(err).ok();
+                None
+            }
+        });
+    let tabs = match initial_tab {
+        Some(tab) => [tab].into_iter().collect::<Tabs>(),
+        None => stdin_tab.into_iter().chain(file_tabs).collect::<Tabs>(),
+    }
+    .with_scrolloff(args.scrolloff)
+    .with_column_widths(args.column_widths.clone().unwrap_or_default().0)
+    .with_date_format(args.date_format.clone());
     let exec_tbl = Commands::default().into_exec();
-    let keybind = Keybind::default();
-    let mut app = App::new(tabs, status_bar, sql_backend, exec_tbl, keybind);
+    let mut keybind = Keybind::default();
+    for (code, modifiers, command) in config.keybinds() {
+        keybind.bind(
+            AppState::Table,
+            *code,
+            *modifiers,
+            AppAction::CommandRun(command.clone()),
+        );
+    }
+    let mut app = App::new(tabs, status_bar, sql_backend, exec_tbl, keybind)
+        .with_confirm(!args.no_confirm)
+        .with_watch(args.watch)
+        .with_read_options(read_options.clone());
 
     // Command handling
 
     // Initialize the terminal user interface.
-    let mut tui = Tui::new(
-        ratatui::Terminal::new(CrosstermBackend::new(io::stderr()))?,
-        EventHandler::new(250),
-    );
+    let backend = CrosstermBackend::new(io::stderr());
+    let terminal = if args.inline {
+        let (_, height) = crossterm::terminal::size()?;
+        ratatui::Terminal::with_options(
+            backend,
+            TerminalOptions {
+                viewport: Viewport::Inline(height),
+            },
+        )?
+    } else {
+        ratatui::Terminal::new(backend)?
+    };
+    let mut tui = Tui::new(terminal, EventHandler::new(args.tick_rate)).with_inline(args.inline);
     tui.init()?;
 
     // Run the main loop
@@ -103,34 +233,6 @@ fn main() -> AppResult<()> {
 
     // Exit the user interface.
     tui.exit()?;
+    fs::remove_dir_all(s3_download_dir()).ok();
     Ok(())
 }
-
-fn read_csv(
-    path: PathBuf,
-    infer_schema: &InferSchema,
-    quote_char: char,
-    separator_char: char,
-    no_header: bool,
-    ignore_errors: bool,
-) -> Result<DataFrame, Box<dyn Error>> {
-    let mut df = CsvReadOptions::default()
-        .with_ignore_errors(ignore_errors)
-        .with_infer_schema_length(infer_schema.into())
-        .with_has_header(!no_header)
-        .with_parse_options(
-            CsvParseOptions::default()
-                .with_quote_char(as_ascii(quote_char))
-                .with_separator(as_ascii(separator_char).expect("Invalid separator")),
-        )
-        .try_into_reader_with_file_path(path.into())?
-        .finish()?;
-    if matches!(infer_schema, InferSchema::Safe) {
-        infer_schema_safe(&mut df);
-    }
-    Ok(df)
-}
-
-fn read_parquet(path: PathBuf) -> Result<DataFrame, Box<dyn Error>> {
-    Ok(ParquetReader::new(File::open(&path)?).set_rechunk(true).finish()?)
-}
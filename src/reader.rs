@@ -0,0 +1,897 @@
+use std::env;
+use std::error::Error;
+use std::ffi::OsStr;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+
+use bzip2::read::BzDecoder;
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser};
+use flate2::read::MultiGzDecoder;
+use polars::datatypes::{DataType, Field};
+use polars::frame::DataFrame;
+use polars::io::csv::read::{CsvParseOptions, CsvReadOptions};
+use polars::io::mmap::MmapBytesReader;
+use polars::io::parquet::read::ParquetReader;
+use polars::io::SerReader;
+use polars::prelude::{JsonLineReader, JsonReader, NamedFrom, Schema};
+use polars::series::Series;
+
+use crate::args::{Args, Dialect, Format, InferSchema};
+mod ods;
+
+use crate::utils::{
+    as_ascii, flatten_struct_columns, infer_schema_safe, infer_separator_from_sample, parse_dtype,
+    parse_thousands,
+};
+
+/// Number of rows loaded from a DSV file in preview mode.
+const DSV_PREVIEW_ROWS: usize = 1000;
+
+/// Most bytes decompressed to preview a compressed DSV file.
+const DSV_PREVIEW_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Byte order mark some editors write at the start of UTF-8 files.
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// Command line arguments that set read options, by id.
+const READ_ARGS: [&str; 11] = [
+    "format",
+    "no_header",
+    "ignore_errors",
+    "infer_schema",
+    "dialect",
+    "separator",
+    "delimiter_inference_rows",
+    "quote_char",
+    "eol",
+    "thousands",
+    "flatten",
+];
+
+/// Options used to read input files into data frames.
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    pub format: Format,
+    pub infer_schema: InferSchema,
+    pub dialect: Option<Dialect>,
+    pub separator: Option<char>,
+    pub separator_inference_rows: usize,
+    pub quote_char: Option<char>,
+    pub eol_char: Option<char>,
+    pub thousands: Option<char>,
+    pub no_header: bool,
+    pub ignore_errors: bool,
+    pub flatten: bool,
+    pub schema: Vec<(String, DataType)>,
+    pub sheet: Option<String>,
+}
+
+impl From<&Args> for ReadOptions {
+    fn from(args: &Args) -> Self {
+        Self {
+            format: args.format.clone(),
+            infer_schema: args.infer_schema.clone(),
+            dialect: args.dialect.clone(),
+            separator: args.separator,
+            separator_inference_rows: args.delimiter_inference_rows,
+            quote_char: args.quote_char,
+            eol_char: args.eol,
+            thousands: args.thousands,
+            no_header: args.no_header,
+            ignore_errors: args.ignore_errors,
+            flatten: args.flatten,
+            schema: Vec::new(),
+            sheet: None,
+        }
+    }
+}
+
+impl Default for ReadOptions {
+    /// Options of the command line defaults.
+    fn default() -> Self {
+        Self::from(&Args::parse_from(["tw"]))
+    }
+}
+
+impl ReadOptions {
+    /// Reads the whole file at `path`.
+    pub fn read(&self, path: &Path) -> Result<DataFrame, Box<dyn Error>> {
+        Ok(self.read_reporting(path, false)?.0)
+    }
+
+    /// Reads the beginning of the file at `path`: the first row group of a
+    /// Parquet file or the first rows of a DSV file.
+    pub fn read_preview(&self, path: &Path) -> Result<DataFrame, Box<dyn Error>> {
+        Ok(self.read_reporting(path, true)?.0)
+    }
+
+    /// Reads the file at `path`, as a preview or in full, along with the
+    /// names of the columns parsed as numbers with thousands separators.
+    pub fn read_reporting(
+        &self,
+        path: &Path,
+        preview: bool,
+    ) -> Result<(DataFrame, Vec<String>), Box<dyn Error>> {
+        let input = if preview && self.format.is_dsv() {
+            open_head(path, DSV_PREVIEW_BYTES)?
+        } else {
+            open(path)?
+        };
+        self.read_input(input, preview)
+    }
+
+    /// Reads the whole standard input. It is buffered in memory since Parquet
+    /// needs to seek to its footer and the DSV separator is detected from its
+    /// first lines.
+    pub fn read_stdin(&self) -> Result<DataFrame, Box<dyn Error>> {
+        Ok(self
+            .read_input(Box::new(read_to_cursor(io::stdin().lock())?), false)?
+            .0)
+    }
+
+    /// Options for the file at `path`, reading it as an ODS spreadsheet if
+    /// its extension is `ods` whatever the format.
+    pub fn for_path(&self, path: &Path) -> Self {
+        let mut options = self.clone();
+        if path
+            .extension()
+            .and_then(OsStr::to_str)
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("ods"))
+        {
+            options.format = Format::Ods;
+        }
+        options
+    }
+
+    /// Options reading each sheet of the ODS spreadsheet at `path`, or these
+    /// options alone for other formats or once a sheet is chosen.
+    pub fn sheets(&self, path: &Path) -> Result<Vec<Self>, Box<dyn Error>> {
+        if !matches!(self.format, Format::Ods) || self.sheet.is_some() {
+            return Ok(vec![self.clone()]);
+        }
+        let sheets = ods::sheet_names(open(path)?)?;
+        if sheets.is_empty() {
+            Err("No sheet found in the OpenDocument file")?
+        }
+        Ok(sheets
+            .into_iter()
+            .map(|sheet| Self {
+                sheet: Some(sheet),
+                ..self.clone()
+            })
+            .collect())
+    }
+
+    /// Options with the read flags among `args`, e.g. `--no-header` or
+    /// `--separator ';'`, replacing the ones of these options.
+    pub fn with_args(&self, args: &[String]) -> Result<Self, String> {
+        let mut command = Args::command().no_binary_name(true);
+        let matches = command.try_get_matches_from_mut(args).map_err(|err| {
+            let err = err.to_string();
+            let line = err.lines().next().unwrap_or_default();
+            line.trim_start_matches("error: ").to_owned()
+        })?;
+        let given = |id: &str| matches.value_source(id) == Some(ValueSource::CommandLine);
+        if command
+            .get_arguments()
+            .map(|arg| arg.get_id().as_str())
+            .any(|id| given(id) && !READ_ARGS.contains(&id))
+        {
+            Err("Only read options, such as --no-header or --separator, can be given")?
+        }
+        let parsed =
+            ReadOptions::from(&Args::from_arg_matches(&matches).map_err(|err| err.to_string())?);
+        let mut options = self.clone();
+        if given("format") {
+            options.format = parsed.format;
+        }
+        if given("no_header") {
+            options.no_header = parsed.no_header;
+        }
+        if given("ignore_errors") {
+            options.ignore_errors = parsed.ignore_errors;
+        }
+        if given("infer_schema") {
+            options.infer_schema = parsed.infer_schema;
+        }
+        if given("dialect") {
+            options.dialect = parsed.dialect;
+        }
+        if given("separator") {
+            options.separator = parsed.separator;
+        }
+        if given("delimiter_inference_rows") {
+            options.separator_inference_rows = parsed.separator_inference_rows;
+        }
+        if given("quote_char") {
+            options.quote_char = parsed.quote_char;
+        }
+        if given("eol") {
+            options.eol_char = parsed.eol_char;
+        }
+        if given("thousands") {
+            options.thousands = parsed.thousands;
+        }
+        if given("flatten") {
+            options.flatten = parsed.flatten;
+        }
+        Ok(options)
+    }
+
+    /// Whether the file at `path` can be partially loaded with
+    /// [`ReadOptions::read_preview`]: DSV files, and Parquet files unless
+    /// compressed since their footer is at the end.
+    pub fn can_preview(&self, path: &Path) -> bool {
+        self.format.is_dsv() || (matches!(self.format, Format::Parquet) && !is_compressed(path))
+    }
+
+    fn read_input(
+        &self,
+        input: Box<dyn MmapBytesReader>,
+        preview: bool,
+    ) -> Result<(DataFrame, Vec<String>), Box<dyn Error>> {
+        let (df, converted) = self.read_format(input, preview)?;
+        let mut df = if self.flatten {
+            flatten_struct_columns(df)?
+        } else {
+            df
+        };
+        for (name, dtype) in self.schema.iter() {
+            if let Ok(series) = df.column(name) {
+                if series.dtype() != dtype {
+                    let series = series.strict_cast(dtype).map_err(|err| {
+                        format!("Unable to cast column '{}' to {}: {}", name, dtype, err)
+                    })?;
+                    df.replace(name, series)?;
+                }
+            }
+        }
+        Ok((df, converted))
+    }
+
+    fn read_format(
+        &self,
+        input: Box<dyn MmapBytesReader>,
+        preview: bool,
+    ) -> Result<(DataFrame, Vec<String>), Box<dyn Error>> {
+        let df = match self.format {
+            Format::Dsv | Format::Csv | Format::Tsv | Format::Psv => {
+                return self.read_csv(input, preview.then_some(DSV_PREVIEW_ROWS));
+            }
+            Format::Parquet if preview => read_parquet_preview(input),
+            Format::Parquet => Ok(ParquetReader::new(input).set_rechunk(true).finish()?),
+            Format::Json | Format::Jsonl if preview => {
+                Err("Preview is not supported for JSON files".into())
+            }
+            Format::Json => Ok(JsonReader::new(Remaining(skip_bom(input)?)).finish()?),
+            Format::Jsonl => Ok(JsonLineReader::new(skip_bom(input)?).finish()?),
+            Format::Ods if preview => Err("Preview is not supported for ODS files".into()),
+            Format::Ods => ods::read_ods(
+                input,
+                self.sheet.as_deref(),
+                !self.no_header,
+                !matches!(self.infer_schema, InferSchema::No),
+            ),
+        }?;
+        Ok((df, Vec::new()))
+    }
+
+    fn read_csv(
+        &self,
+        input: Box<dyn MmapBytesReader>,
+        n_rows: Option<usize>,
+    ) -> Result<(DataFrame, Vec<String>), Box<dyn Error>> {
+        let mut input = skip_bom(input)?;
+        let start = input.stream_position()?;
+        let quote_char = self
+            .quote_char
+            .or(self.dialect.as_ref().map(Dialect::quote_char))
+            .unwrap_or('"');
+        let separator_char = match self
+            .separator
+            .or(self.format.separator())
+            .or(self.dialect.as_ref().map(Dialect::separator))
+        {
+            Some(separator) => separator,
+            None => {
+                let separator = infer_separator(&mut input, self.separator_inference_rows);
+                input.seek(SeekFrom::Start(start))?;
+                separator.unwrap_or(',')
+            }
+        };
+        let eol_char = self.eol_char.unwrap_or('\n');
+        let options = CsvReadOptions::default()
+            .with_ignore_errors(self.ignore_errors)
+            .with_infer_schema_length((&self.infer_schema).into())
+            .with_has_header(!self.no_header)
+            .with_parse_options(
+                CsvParseOptions::default()
+                    .with_quote_char(as_ascii(quote_char))
+                    .with_separator(as_ascii(separator_char).ok_or("Invalid separator")?)
+                    .with_eol_char(as_ascii(eol_char).ok_or("Invalid line terminator")?)
+                    .with_truncate_ragged_lines(
+                        self.dialect
+                            .as_ref()
+                            .is_some_and(Dialect::truncate_ragged_lines),
+                    ),
+            );
+        let schema_overwrite = if self.schema.is_empty() {
+            None
+        } else {
+            // Overwrites are matched by position when there are as many as
+            // columns, so only the ones in the header are passed.
+            let header = options
+                .clone()
+                .with_n_rows(Some(0))
+                .into_reader_with_file_handle(&mut input)
+                .finish()?;
+            input.seek(SeekFrom::Start(start))?;
+            Some(Arc::new(Schema::from_iter(
+                self.schema
+                    .iter()
+                    .filter(|(name, _)| header.column(name).is_ok())
+                    .map(|(name, dtype)| {
+                        // Other types are cast from strings once the file is read
+                        match dtype {
+                            DataType::Int32
+                            | DataType::Int64
+                            | DataType::UInt32
+                            | DataType::UInt64
+                            | DataType::Float32
+                            | DataType::Float64
+                            | DataType::Boolean => Field::new(name, dtype.clone()),
+                            _ => Field::new(name, DataType::String),
+                        }
+                    }),
+            )))
+        };
+        let mut df = options
+            .with_n_rows(n_rows)
+            .with_schema_overwrite(schema_overwrite)
+            .into_reader_with_file_handle(input)
+            .finish()?;
+        if matches!(self.infer_schema, InferSchema::Safe) {
+            let declared = df
+                .get_columns()
+                .iter()
+                .filter(|series| self.schema.iter().any(|(name, _)| name == series.name()))
+                .cloned()
+                .collect::<Vec<_>>();
+            infer_schema_safe(&mut df);
+            for series in declared {
+                let name = series.name().to_owned();
+                df.replace(&name, series)?;
+            }
+        }
+        // Parsed after the safe inference, which would cast decimals to integers
+        let converted = match self.thousands {
+            Some(separator) if !matches!(self.infer_schema, InferSchema::No) => {
+                let declared = self
+                    .schema
+                    .iter()
+                    .map(|(name, _)| name.as_str())
+                    .collect::<Vec<_>>();
+                parse_thousands(&mut df, separator, &declared)
+            }
+            _ => Vec::new(),
+        };
+        Ok((df, converted))
+    }
+}
+
+/// Reads a schema file, a JSON object mapping column names to data types,
+/// e.g. `{"zip": "str", "price": "f64"}`.
+pub fn read_schema(path: &Path) -> Result<Vec<(String, DataType)>, Box<dyn Error>> {
+    let df = JsonReader::new(Remaining(skip_bom(Box::new(File::open(path)?))?)).finish()?;
+    df.get_columns()
+        .iter()
+        .map(|series| {
+            let name = series.name().to_owned();
+            let dtype = series
+                .str()
+                .ok()
+                .and_then(|values| values.get(0))
+                .and_then(parse_dtype)
+                .ok_or(format!("Invalid data type for column '{}' in the schema", name))?;
+            Ok((name, dtype))
+        })
+        .collect()
+}
+
+/// Detects the separator of DSV content from its first `rows` lines.
+pub fn infer_separator(input: impl Read, rows: usize) -> Option<char> {
+    let sample = BufReader::new(input)
+        .lines()
+        .take(rows)
+        .map_while(Result::ok)
+        .collect::<Vec<_>>();
+    infer_separator_from_sample(&sample)
+}
+
+/// Reads the column names and data types of the Parquet file, and its number
+/// of rows, from its footer without reading any of its data.
+/// Compressed files are rejected, as they would be decompressed in full first.
+pub fn read_parquet_schema(path: &Path) -> Result<(DataFrame, usize), Box<dyn Error>> {
+    if is_compressed(path) {
+        Err(format!(
+            "Schema only is not supported for compressed files: {}",
+            path.display()
+        ))?
+    }
+    let mut reader = ParquetReader::new(File::open(path)?);
+    let rows = reader.num_rows()?;
+    let (names, types): (Vec<_>, Vec<_>) = reader
+        .schema()?
+        .fields
+        .iter()
+        .map(|field| {
+            (
+                field.name.to_owned(),
+                DataType::from_arrow(&field.data_type, true).to_string(),
+            )
+        })
+        .unzip();
+    let df = DataFrame::new(vec![
+        Series::new("Column", names),
+        Series::new("Type", types),
+    ])?;
+    Ok((df, rows))
+}
+
+/// Reads only the first row group of the Parquet input.
+fn read_parquet_preview(input: Box<dyn MmapBytesReader>) -> Result<DataFrame, Box<dyn Error>> {
+    let mut reader = ParquetReader::new(input);
+    let rows = reader
+        .get_metadata()?
+        .row_groups
+        .first()
+        .map(|row_group| row_group.num_rows())
+        .unwrap_or_default();
+    Ok(reader
+        .with_slice(Some((0, rows)))
+        .set_rechunk(true)
+        .finish()?)
+}
+
+/// Reads the per column storage statistics of the Parquet file from its footer,
+/// summed over all row groups.
+pub fn read_parquet_metadata(path: PathBuf) -> Result<DataFrame, Box<dyn Error>> {
+    let mut reader = ParquetReader::new(open(&path)?);
+    let metadata = reader.get_metadata()?;
+    let mut columns = Vec::<(String, String, Vec<String>, i64, i64)>::new();
+    for row_group in metadata.row_groups.iter() {
+        for (idx, column) in row_group.columns().iter().enumerate() {
+            if columns.len() <= idx {
+                columns.push((
+                    column.descriptor().path_in_schema.join("."),
+                    format!("{:?}", column.physical_type()),
+                    Vec::new(),
+                    0,
+                    0,
+                ));
+            }
+            let (_, _, codecs, compressed, uncompressed) = &mut columns[idx];
+            let codec = format!("{:?}", column.compression());
+            if !codecs.contains(&codec) {
+                codecs.push(codec);
+            }
+            *compressed += column.compressed_size();
+            *uncompressed += column.uncompressed_size();
+        }
+    }
+    let (names, types, codecs, compressed, uncompressed) = columns.into_iter().fold(
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new()),
+        |(mut vn, mut vt, mut vc, mut vcs, mut vus), (n, t, c, cs, us)| {
+            vn.push(n);
+            vt.push(t);
+            vc.push(c.join(", "));
+            vcs.push(cs);
+            vus.push(us);
+            (vn, vt, vc, vcs, vus)
+        },
+    );
+    Ok(DataFrame::new(vec![
+        Series::new("Column", names),
+        Series::new("Physical Type", types),
+        Series::new("Codec", codecs),
+        Series::new("Compressed Bytes", compressed),
+        Series::new("Uncompressed Bytes", uncompressed),
+    ])?)
+}
+
+/// Opens the file at `path`, transparently decompressing it if its extension
+/// is one of `gz`, `zst`, or `bz2`.
+pub fn open(path: &Path) -> Result<Box<dyn MmapBytesReader>, Box<dyn Error>> {
+    open_decompressed(path, File::open(path)?)
+}
+
+fn open_decompressed(
+    path: &Path,
+    input: impl MmapBytesReader + 'static,
+) -> Result<Box<dyn MmapBytesReader>, Box<dyn Error>> {
+    Ok(if is_compressed(path) {
+        Box::new(read_to_cursor(decoder(path, input)?)?)
+    } else {
+        Box::new(input)
+    })
+}
+
+/// Opens the file at `path` like [`open`], but decompresses at most `limit`
+/// bytes of it, cut after the last complete line, to preview compressed DSV
+/// files without holding them in memory.
+fn open_head(path: &Path, limit: u64) -> Result<Box<dyn MmapBytesReader>, Box<dyn Error>> {
+    if !is_compressed(path) {
+        return open(path);
+    }
+    let mut buf = Vec::new();
+    decoder(path, File::open(path)?)?
+        .take(limit)
+        .read_to_end(&mut buf)?;
+    if buf.len() as u64 == limit {
+        let end = buf
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |idx| idx + 1);
+        buf.truncate(end);
+    }
+    Ok(Box::new(Cursor::new(buf)))
+}
+
+/// Whether the file at `path` holds more than `limit` bytes of data once
+/// decompressed. Compressed files are streamed through their decoder up to
+/// the limit rather than loaded, since their size on disk says little.
+pub fn exceeds_size(path: &Path, limit: u64) -> Result<bool, Box<dyn Error>> {
+    let file = File::open(path)?;
+    if !is_compressed(path) {
+        return Ok(file.metadata()?.len() > limit);
+    }
+    let read = io::copy(
+        &mut decoder(path, file)?.take(limit.saturating_add(1)),
+        &mut io::sink(),
+    )?;
+    Ok(read > limit)
+}
+
+fn is_compressed(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("gz" | "zst" | "bz2")
+    )
+}
+
+/// Decompressing reader of the input, by the extension of `path`.
+fn decoder(path: &Path, input: impl Read + 'static) -> Result<Box<dyn Read>, Box<dyn Error>> {
+    Ok(match path.extension().and_then(OsStr::to_str) {
+        Some("gz") => Box::new(MultiGzDecoder::new(input)),
+        Some("zst") => Box::new(zstd::Decoder::new(input)?),
+        Some("bz2") => Box::new(BzDecoder::new(input)),
+        _ => Box::new(input),
+    })
+}
+
+/// Downloads the object at the `s3://` URL with the AWS CLI, which takes the
+/// credentials and region from the standard AWS environment variables and
+/// configuration files, into [`s3_download_dir`]. The object is streamed to
+/// disk and only downloaded once per session, so reloads read the local copy.
+fn download_s3_object(url: &str) -> Result<PathBuf, String> {
+    let path = url
+        .trim_start_matches("s3://")
+        .split('/')
+        .filter(|part| !matches!(*part, "" | "." | ".."))
+        .fold(s3_download_dir(), |path, part| path.join(part));
+    if path.is_file() {
+        return Ok(path);
+    }
+    let file_name = path
+        .file_name()
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| format!("Invalid S3 URL {}", url))?;
+    let partial = path.with_file_name(format!(".{}.part", file_name));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|err| format!("Unable to create {}: {}", parent.display(), err))?;
+    }
+    let output = Command::new("aws")
+        .args(["s3", "cp", "--only-show-errors", url])
+        .arg(&partial)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|err| match err.kind() {
+            io::ErrorKind::NotFound => format!(
+                "Unable to read {}: the AWS CLI (aws) is not installed or not in PATH, see https://aws.amazon.com/cli/",
+                url
+            ),
+            _ => format!("Unable to run the AWS CLI (aws) to read {}: {}", url, err),
+        })?;
+    if !output.status.success() {
+        fs::remove_file(&partial).ok();
+        Err(format!(
+            "Unable to read {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))?
+    }
+    fs::rename(&partial, &path)
+        .map_err(|err| format!("Unable to save {}: {}", path.display(), err))?;
+    Ok(path)
+}
+
+/// Directory the `s3://` objects read in this session are downloaded to.
+pub fn s3_download_dir() -> PathBuf {
+    env::temp_dir().join(format!("tabiew-s3-{}", std::process::id()))
+}
+
+/// Moves the input past the UTF-8 byte order mark at its start, if any, so it
+/// neither ends up in the first column name nor trips the JSON readers. Files
+/// are memory mapped from their position by the DSV and JSON lines readers;
+/// other inputs, already in memory, are read from it instead.
+fn skip_bom(
+    mut input: Box<dyn MmapBytesReader>,
+) -> Result<Box<dyn MmapBytesReader>, Box<dyn Error>> {
+    let mut head = Vec::with_capacity(UTF8_BOM.len());
+    input
+        .by_ref()
+        .take(UTF8_BOM.len() as u64)
+        .read_to_end(&mut head)?;
+    if head != UTF8_BOM {
+        input.rewind()?;
+        Ok(input)
+    } else if input.to_file().is_some() {
+        Ok(input)
+    } else {
+        Ok(Box::new(Remaining(input)))
+    }
+}
+
+/// Input read from its position on, rather than memory mapped or borrowed
+/// whole from its start. The JSON reader copies its input in any case.
+struct Remaining<R>(R);
+
+impl<R: Read> Read for Remaining<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R: Seek> Seek for Remaining<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.0.seek(pos)
+    }
+}
+
+impl<R: MmapBytesReader> MmapBytesReader for Remaining<R> {}
+
+/// Reads the whole input into memory, to seek through it.
+fn read_to_cursor(mut reader: impl Read) -> Result<Cursor<Vec<u8>>, Box<dyn Error>> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    Ok(Cursor::new(buf))
+}
+
+/// Path with a leading `~` replaced by the home directory and `$VAR` or
+/// `${VAR}` by the environment variable, e.g. `~/data/$USER.csv`, checking
+/// that the file exists. An `s3://` URL resolves to a local copy of the
+/// object.
+pub fn resolve_path(path: &Path) -> Result<PathBuf, String> {
+    let Some(text) = path.to_str() else {
+        return Ok(path.to_owned());
+    };
+    let expanded = expand_path(text)?;
+    if expanded.starts_with("s3://") {
+        return download_s3_object(&expanded);
+    }
+    if !Path::new(&expanded).exists() {
+        if expanded == text {
+            Err(format!("File not found: {}", text))?
+        }
+        Err(format!(
+            "File not found: {} (expanded from {})",
+            expanded, text
+        ))?
+    }
+    Ok(PathBuf::from(expanded))
+}
+
+fn expand_path(text: &str) -> Result<String, String> {
+    let mut expanded = String::with_capacity(text.len());
+    let rest = match text.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', std::path::MAIN_SEPARATOR]) => {
+            let home = env::var("HOME")
+                .or_else(|_| env::var("USERPROFILE"))
+                .map_err(|_| format!("Unable to expand ~ in {}: no home directory", text))?;
+            expanded.push_str(&home);
+            rest
+        }
+        _ => text,
+    };
+    let mut chars = rest.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '$' {
+            expanded.push(c);
+            continue;
+        }
+        let braced = chars.next_if_eq(&'{').is_some();
+        let mut name = String::new();
+        while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+            name.push(c);
+        }
+        if name.is_empty() || (braced && chars.next_if_eq(&'}').is_none()) {
+            // Not a variable, e.g. a lone `$`
+            expanded.push('$');
+            if braced {
+                expanded.push('{');
+            }
+            expanded.push_str(&name);
+            continue;
+        }
+        let value = env::var(&name)
+            .map_err(|_| format!("Unable to expand ${} in {}: variable not set", name, text))?;
+        expanded.push_str(&value);
+    }
+    Ok(expanded)
+}
+
+/// Name of the table of a sheet of the spreadsheet at `path`, after both the
+/// file and the sheet if the spreadsheet has several, e.g. `budget_2024`.
+pub fn sheet_table_name(path: &Path, sheet: Option<&str>, sheets: usize) -> Option<String> {
+    let name = table_name(path)?;
+    Some(match sheet {
+        Some(sheet) if sheets > 1 => format!("{}_{}", name, sheet),
+        _ => name,
+    })
+}
+
+/// File name without the format and compression extensions, e.g. `data` for `data.csv.gz`.
+pub fn table_name(path: &Path) -> Option<String> {
+    let path = match path.extension().and_then(OsStr::to_str) {
+        Some("gz" | "zst" | "bz2") => Path::new(path.file_stem()?),
+        _ => path,
+    };
+    Some(path.file_stem()?.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_with_args() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let options = ReadOptions::default()
+            .with_args(&args(&["--no-header", "--separator", ";"]))
+            .unwrap();
+        assert!(options.no_header);
+        assert_eq!(options.separator, Some(';'));
+        let options = options.with_args(&args(&["--ignore-errors"])).unwrap();
+        assert!(options.no_header && options.ignore_errors);
+        assert_eq!(options.separator, Some(';'));
+        assert!(ReadOptions::default()
+            .with_args(&args(&["--theme", "argonaut"]))
+            .is_err());
+    }
+
+    #[test]
+    fn test_dialects() {
+        let read = |dialect: Dialect, text: &str| {
+            ReadOptions {
+                dialect: Some(dialect),
+                ..ReadOptions::default()
+            }
+            .read_csv(Box::new(Cursor::new(text.as_bytes().to_vec())), None)
+            .map(|(df, _)| df)
+        };
+        let ragged = "a,b\r\n1,x\r\n2,y,extra\r\n";
+        let df = read(Dialect::Excel, ragged).unwrap();
+        assert_eq!(df.shape(), (2, 2));
+        assert_eq!(df.column("b").unwrap(), &Series::new("b", ["x", "y"]));
+        assert!(read(Dialect::Rfc4180, ragged).is_err());
+        let df = read(Dialect::ExcelTab, "a\tb\n\"1,2\"\tx\n").unwrap();
+        assert_eq!(df.column("a").unwrap(), &Series::new("a", ["1,2"]));
+        assert_eq!(read(Dialect::Excel, "a\tb\n1\tx\n").unwrap().width(), 1);
+    }
+
+    #[test]
+    fn test_skip_bom() {
+        let read = |format: Format, text: &str| {
+            ReadOptions {
+                format,
+                ..ReadOptions::default()
+            }
+            .read_input(
+                Box::new(Cursor::new(format!("\u{feff}{}", text).into_bytes())),
+                false,
+            )
+            .unwrap()
+            .0
+        };
+        let df = read(Format::Dsv, "name;n\nx;1\n");
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        let df = read(Format::Csv, "name,n\nx,1\n");
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        let df = read(Format::Json, r#"[{"name": "x", "n": 1}]"#);
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        let df = read(Format::Jsonl, r#"{"name": "x", "n": 1}"#);
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+    }
+
+    #[test]
+    fn test_skip_bom_in_files() {
+        let path = env::temp_dir().join(format!("tabiew-test-bom-{}", std::process::id()));
+        let read = |format: Format, text: &str| {
+            fs::write(&path, format!("\u{feff}{}", text)).unwrap();
+            ReadOptions {
+                format,
+                schema: vec![("n".to_owned(), DataType::String)],
+                ..ReadOptions::default()
+            }
+            .read(&path)
+            .unwrap()
+        };
+        let df = read(Format::Dsv, "name;n\nx;1\n");
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        assert_eq!(df.column("n").unwrap().dtype(), &DataType::String);
+        let df = read(Format::Json, r#"[{"name": "x", "n": 1}]"#);
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        let df = read(Format::Jsonl, r#"{"name": "x", "n": 1}"#);
+        assert_eq!(df.get_column_names(), ["name", "n"]);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_compressed_parquet_schema() {
+        assert_eq!(
+            read_parquet_schema(Path::new("data.parquet.gz"))
+                .unwrap_err()
+                .to_string(),
+            "Schema only is not supported for compressed files: data.parquet.gz"
+        );
+    }
+
+    #[test]
+    fn test_compressed_size() {
+        use flate2::{write::GzEncoder, Compression};
+        use std::io::Write;
+
+        let path = env::temp_dir().join(format!("tabiew-test-{}.csv.gz", std::process::id()));
+        let mut encoder = GzEncoder::new(File::create(&path).unwrap(), Compression::default());
+        encoder.write_all(b"n\n").unwrap();
+        for _ in 0..10_000 {
+            encoder.write_all(b"12345\n").unwrap();
+        }
+        encoder.finish().unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() < 1000);
+        assert!(exceeds_size(&path, 1000).unwrap());
+        assert!(!exceeds_size(&path, 100_000).unwrap());
+        let df = ReadOptions::default()
+            .read_input(open_head(&path, 100).unwrap(), true)
+            .unwrap()
+            .0;
+        assert_eq!(df.shape(), (16, 1));
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_expand_path() {
+        env::set_var("TABIEW_TEST_DIR", "/data");
+        assert_eq!(
+            expand_path("$TABIEW_TEST_DIR/a.csv").unwrap(),
+            "/data/a.csv"
+        );
+        assert_eq!(
+            expand_path("${TABIEW_TEST_DIR}_x/a.csv").unwrap(),
+            "/data_x/a.csv"
+        );
+        assert_eq!(expand_path("a$.csv").unwrap(), "a$.csv");
+        assert_eq!(expand_path("~user/a.csv").unwrap(), "~user/a.csv");
+        assert!(expand_path("$TABIEW_TEST_UNSET/a.csv")
+            .unwrap_err()
+            .contains("$TABIEW_TEST_UNSET"));
+        if let Ok(home) = env::var("HOME") {
+            assert_eq!(expand_path("~/a.csv").unwrap(), format!("{}/a.csv", home));
+        }
+    }
+}
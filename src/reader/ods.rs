@@ -0,0 +1,206 @@
+use std::error::Error;
+use std::io::{Cursor, Read};
+
+use calamine::{Data, Ods, Reader};
+use itertools::Itertools;
+use polars::frame::DataFrame;
+use polars::prelude::NamedFrom;
+use polars::series::Series;
+
+/// Names of the sheets of an OpenDocument spreadsheet, in their order.
+pub fn sheet_names(input: impl Read) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(open_ods(input)?.sheet_names())
+}
+
+/// Reads the named sheet of an OpenDocument spreadsheet, or its first sheet.
+/// Columns of numbers or booleans are typed as such unless `infer` is off;
+/// others are strings.
+pub fn read_ods(
+    input: impl Read,
+    sheet: Option<&str>,
+    has_header: bool,
+    infer: bool,
+) -> Result<DataFrame, Box<dyn Error>> {
+    let mut ods = open_ods(input)?;
+    let sheet = match sheet {
+        Some(sheet) => sheet.to_owned(),
+        None => ods
+            .sheet_names()
+            .into_iter()
+            .next()
+            .ok_or("No sheet found in the OpenDocument file")?,
+    };
+    let range = ods
+        .worksheet_range(&sheet)
+        .map_err(|err| format!("Unable to read sheet '{}': {}", sheet, err))?;
+    let width = range.width();
+    let mut rows = range.rows();
+    let header = if has_header {
+        rows.next().unwrap_or_default()
+    } else {
+        &[]
+    };
+    let rows = rows.collect_vec();
+
+    let columns = column_names(header, width)
+        .iter()
+        .enumerate()
+        .map(|(col, name)| {
+            let cells = rows
+                .iter()
+                .map(|row| row.get(col).unwrap_or(&Data::Empty))
+                .collect_vec();
+            column(name, &cells, infer)
+        })
+        .collect_vec();
+    Ok(DataFrame::new(columns)?)
+}
+
+/// Names of the columns after the header cells, `column_<n>` for empty ones,
+/// with the first free `_duplicated_<n>` suffix on repeated ones.
+fn column_names(header: &[Data], width: usize) -> Vec<String> {
+    let mut names = Vec::<String>::with_capacity(width);
+    for col in 0..width {
+        let name = match header.get(col).map(Data::to_string) {
+            Some(name) if !name.is_empty() => name,
+            _ => format!("column_{}", col + 1),
+        };
+        let name = if names.contains(&name) {
+            (0..)
+                .map(|idx| format!("{}_duplicated_{}", name, idx))
+                .find(|name| !names.contains(name))
+                .unwrap_or_default()
+        } else {
+            name
+        };
+        names.push(name);
+    }
+    names
+}
+
+/// The archive is read in memory since it is seeked through, and the input
+/// may be decompressed on the fly.
+fn open_ods(mut input: impl Read) -> Result<Ods<Cursor<Vec<u8>>>, Box<dyn Error>> {
+    let mut bytes = Vec::new();
+    input.read_to_end(&mut bytes)?;
+    Ok(
+        Ods::new(Cursor::new(bytes))
+            .map_err(|err| format!("Invalid OpenDocument file: {}", err))?,
+    )
+}
+
+fn column(name: &str, cells: &[&Data], infer: bool) -> Series {
+    let mut filled = cells
+        .iter()
+        .filter(|cell| ***cell != Data::Empty)
+        .peekable();
+    let (numbers, bools) = match filled.peek() {
+        Some(_) if infer => filled.fold((true, true), |(numbers, bools), cell| {
+            (
+                numbers && matches!(cell, Data::Int(_) | Data::Float(_)),
+                bools && matches!(cell, Data::Bool(_)),
+            )
+        }),
+        _ => (false, false),
+    };
+    if numbers {
+        let floats = cells
+            .iter()
+            .map(|cell| match cell {
+                Data::Int(value) => Some(*value as f64),
+                Data::Float(value) => Some(*value),
+                _ => None,
+            })
+            .collect_vec();
+        if floats.iter().flatten().all(|value| value.fract() == 0.0) {
+            Series::new(
+                name,
+                floats
+                    .into_iter()
+                    .map(|value| value.map(|value| value as i64))
+                    .collect_vec(),
+            )
+        } else {
+            Series::new(name, floats)
+        }
+    } else if bools {
+        Series::new(
+            name,
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    Data::Bool(value) => Some(*value),
+                    _ => None,
+                })
+                .collect_vec(),
+        )
+    } else {
+        Series::new(
+            name,
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    Data::Empty => None,
+                    cell => Some(cell.to_string()),
+                })
+                .collect_vec(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use polars::prelude::*;
+
+    use super::*;
+
+    const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/data/sheets.ods");
+
+    #[test]
+    fn test_sheet_names() {
+        assert_eq!(
+            sheet_names(File::open(FIXTURE).unwrap()).unwrap(),
+            ["people", "flags"]
+        );
+    }
+
+    #[test]
+    fn test_read_ods() {
+        let df = read_ods(File::open(FIXTURE).unwrap(), None, true, true).unwrap();
+        let expected = df!(
+            "name" => ["Ada & Bob", "Cy", "Di"],
+            "age" => [Some(36i64), None, Some(41)],
+            "score" => [1.5, 2.0, 3.25],
+        )
+        .unwrap();
+        assert_eq!(df, expected);
+
+        let df = read_ods(File::open(FIXTURE).unwrap(), Some("flags"), true, true).unwrap();
+        assert_eq!(df, df!("flag" => [true, false]).unwrap());
+
+        let df = read_ods(File::open(FIXTURE).unwrap(), Some("flags"), false, false).unwrap();
+        assert_eq!(df, df!("column_1" => ["flag", "true", "false"]).unwrap());
+
+        assert!(read_ods(File::open(FIXTURE).unwrap(), Some("other"), true, true).is_err());
+    }
+
+    #[test]
+    fn test_column_names() {
+        let header =
+            ["a", "a", "", "a", "a_duplicated_1"].map(|name| Data::String(name.to_owned()));
+        assert_eq!(
+            column_names(&header, 7),
+            [
+                "a",
+                "a_duplicated_0",
+                "column_3",
+                "a_duplicated_1",
+                "a_duplicated_1_duplicated_0",
+                "column_6",
+                "column_7",
+            ]
+        );
+    }
+}
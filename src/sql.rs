@@ -2,9 +2,9 @@ use std::{collections::BTreeMap, path::PathBuf};
 
 use itertools::Itertools;
 use polars::{
-    error::PolarsResult,
+    error::{polars_err, PolarsResult},
     frame::DataFrame,
-    prelude::{IntoLazy, LazyFrame, NamedFrom},
+    prelude::{InProcessQuery, IntoLazy, LazyFrame, NamedFrom},
     series::Series,
 };
 use polars_sql::SQLContext;
@@ -12,6 +12,7 @@ use polars_sql::SQLContext;
 pub struct SqlBackend {
     sql: SQLContext,
     tables: BTreeMap<String, (String, PathBuf)>,
+    variables: BTreeMap<String, String>,
 }
 
 impl SqlBackend {
@@ -19,6 +20,7 @@ impl SqlBackend {
         Self {
             sql: SQLContext::new(),
             tables: Default::default(),
+            variables: Default::default(),
         }
     }
 
@@ -59,9 +61,112 @@ impl SqlBackend {
         }
     }
 
+    pub fn path(&self, name: &str) -> Option<&PathBuf> {
+        self.tables.get(name).map(|(_, path)| path)
+    }
+
+    pub fn update(&mut self, name: &str, data_frame: DataFrame) -> PolarsResult<()> {
+        let (structure, _) = self
+            .tables
+            .get_mut(name)
+            .ok_or_else(|| polars_err!(SQLInterface: "Table '{}' not found", name))?;
+        *structure = data_frame_structure(&data_frame);
+        self.sql.register(name, data_frame.lazy());
+        Ok(())
+    }
+
     pub fn execute(&mut self, query: &str) -> PolarsResult<DataFrame> {
         self.sql.execute(query).and_then(LazyFrame::collect)
     }
+
+    /// Starts running the query on the thread pool, returning a handle to
+    /// fetch its result from or cancel it with.
+    pub fn execute_concurrently(&mut self, query: &str) -> PolarsResult<InProcessQuery> {
+        self.sql.execute(query)?.collect_concurrently()
+    }
+
+    /// Optimized query plan of the query, one line per row, without running it.
+    pub fn explain(&mut self, query: &str) -> PolarsResult<DataFrame> {
+        let plan = self.sql.execute(query)?.explain(true)?;
+        DataFrame::new(vec![Series::new("Plan", plan.lines().collect_vec())])
+    }
+
+    /// Sets the variable to the SQL literal, e.g. `'EU'` or `5`.
+    pub fn set_variable(&mut self, name: &str, literal: &str) {
+        self.variables.insert(name.to_owned(), literal.to_owned());
+    }
+
+    /// Removes the variable, returning whether it was set.
+    pub fn unset_variable(&mut self, name: &str) -> bool {
+        self.variables.remove(name).is_some()
+    }
+
+    pub fn variables(&self) -> impl Iterator<Item = (&String, &String)> {
+        self.variables.iter()
+    }
+
+    /// Replaces the `$name` references of the query with the literals of the
+    /// variables, except in string literals, quoted identifiers, and comments.
+    /// Only queries typed by the user are substituted, once, before running.
+    pub fn substitute(&self, query: &str) -> PolarsResult<String> {
+        let mut text = String::with_capacity(query.len());
+        let mut chars = query.chars().peekable();
+        let mut quote = None;
+        while let Some(c) = chars.next() {
+            match (quote, c) {
+                (Some(q), c) if c == q => quote = None,
+                (Some(_), _) => (),
+                (None, '\'' | '"') => quote = Some(c),
+                (None, '-') if chars.peek() == Some(&'-') => quote = Some('\n'),
+                (None, '$')
+                    if chars
+                        .peek()
+                        .is_some_and(|c| c.is_ascii_alphabetic() || *c == '_') =>
+                {
+                    let mut name = String::new();
+                    while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                        name.push(c);
+                    }
+                    let literal = self.variables.get(&name).ok_or_else(|| {
+                        polars_err!(
+                            SQLInterface: "Unknown variable '${}', set it with :set {} = <value>",
+                            name, name
+                        )
+                    })?;
+                    text.push_str(literal);
+                    continue;
+                }
+                _ => (),
+            }
+            text.push(c);
+        }
+        Ok(text)
+    }
+}
+
+/// SQL literal of a variable value: quoted text, with `'` or `"`, becomes a
+/// string literal, while numbers, booleans, and `NULL` are kept as is.
+pub fn sql_literal(value: &str) -> Result<String, String> {
+    let text = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .or_else(|| {
+            value
+                .strip_prefix('\'')
+                .and_then(|value| value.strip_suffix('\''))
+        });
+    match text {
+        Some(text) => Ok(format!("'{}'", text.replace('\'', "''"))),
+        None if value.parse::<f64>().is_ok()
+            || ["true", "false", "null"].contains(&value.to_lowercase().as_str()) =>
+        {
+            Ok(value.to_owned())
+        }
+        None => Err(format!(
+            "Invalid value {}, quote text values, e.g. \"{}\"",
+            value, value
+        )),
+    }
 }
 
 impl Default for SqlBackend {
@@ -118,6 +223,53 @@ mod tests {
         assert_eq!(name_gen.next().unwrap(), "student_4");
     }
 
+    #[test]
+    fn test_explain() {
+        let mut sql = SqlBackend::new();
+        sql.register("t", df!["a" => [1, 2, 3]].unwrap(), "t.csv".into());
+        let plan = sql.explain("SELECT a FROM t WHERE a > 1").unwrap();
+        assert_eq!(plan.get_column_names(), ["Plan"]);
+        assert!(plan
+            .column("Plan")
+            .unwrap()
+            .str()
+            .unwrap()
+            .into_iter()
+            .flatten()
+            .any(|line| line.contains("SELECTION")));
+        assert!(sql.explain("SELECT b FROM t").is_err());
+    }
+
+    #[test]
+    fn test_variables() {
+        let mut sql = SqlBackend::new();
+        let df = df!["region" => ["EU", "US", "EU"]].unwrap();
+        sql.register("t", df, "t.csv".into());
+        sql.set_variable("region", &sql_literal("\"EU\"").unwrap());
+        assert_eq!(
+            sql.substitute("SELECT '$region', \"$region\" FROM t WHERE region = $region -- $x")
+                .unwrap(),
+            "SELECT '$region', \"$region\" FROM t WHERE region = 'EU' -- $x"
+        );
+        let query = sql
+            .substitute("SELECT * FROM t WHERE region = $region")
+            .unwrap();
+        assert_eq!(sql.execute(&query).unwrap().height(), 2);
+        // Generated queries run as they are, whatever the names they refer to
+        let df = df!["region" => ["EU"]].unwrap();
+        let name = sql.register("t$region", df, "t$region.csv".into());
+        let query = format!("SELECT * FROM {}", name);
+        assert_eq!(sql.execute(&query).unwrap().height(), 1);
+        assert_eq!(
+            sql.substitute("SELECT $other").unwrap_err().to_string(),
+            "Unknown variable '$other', set it with :set other = <value>"
+        );
+        assert_eq!(sql_literal("'it's'").unwrap(), "'it''s'");
+        assert_eq!(sql_literal("1.5").unwrap(), "1.5");
+        assert_eq!(sql_literal("NULL").unwrap(), "NULL");
+        assert!(sql_literal("EU").is_err());
+    }
+
     #[test]
     fn test_data_frame_structure() {
         // Create a sample DataFrame
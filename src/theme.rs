@@ -5,6 +5,10 @@ pub trait Styler {
     fn table_header_cell(col: usize) -> Style;
     fn table_row(row: usize) -> Style;
     fn table_highlight() -> Style;
+    fn table_bookmark() -> Style;
+    fn table_gutter() -> Style;
+    fn table_bar(col: usize) -> Style;
+    fn table_bool(value: bool) -> Style;
     fn sheet_value() -> Style;
     fn status_bar_error() -> Style;
     fn status_bar_prompt() -> Style;
@@ -56,6 +60,28 @@ where
             .fg(Self::HIGHTLIGHT_FOREGROUND)
     }
 
+    fn table_bookmark() -> Style {
+        Style::new()
+            .bg(Self::LIGHT_BACKGROUND)
+            .fg(Self::COLORS[2])
+    }
+
+    fn table_gutter() -> Style {
+        Style::new().fg(Self::LIGHT_BACKGROUND)
+    }
+
+    fn table_bar(col: usize) -> Style {
+        Style::new().fg(Self::DARK_COLORS[col % Self::DARK_COLORS.len()])
+    }
+
+    fn table_bool(value: bool) -> Style {
+        Style::new().fg(if value {
+            Self::COLORS[3]
+        } else {
+            Self::COLORS[0]
+        })
+    }
+
     fn sheet_value() -> Style {
         Style::default().fg(Self::FOREGROUND)
     }
@@ -180,6 +206,22 @@ impl Styler for Terminal {
         Style::default().bg(Color::Yellow).fg(Color::Black)
     }
 
+    fn table_bookmark() -> Style {
+        Style::default().bg(Color::Magenta).fg(Color::Black)
+    }
+
+    fn table_gutter() -> Style {
+        Style::default().dim()
+    }
+
+    fn table_bar(_col: usize) -> Style {
+        Style::default().fg(Color::Cyan)
+    }
+
+    fn table_bool(value: bool) -> Style {
+        Style::default().fg(if value { Color::Green } else { Color::Red })
+    }
+
     fn sheet_value() -> Style {
         Style::default()
     }
@@ -17,12 +17,26 @@ pub struct Tui<B: Backend> {
     terminal: Terminal<B>,
     /// Terminal event handler.
     pub events: EventHandler,
+    /// Whether to render in the main screen instead of the alternate one,
+    /// leaving the last frame in the scrollback.
+    inline: bool,
 }
 
 impl<B: Backend> Tui<B> {
     /// Constructs a new instance of [`Tui`].
     pub fn new(terminal: Terminal<B>, events: EventHandler) -> Self {
-        Self { terminal, events }
+        Self {
+            terminal,
+            events,
+            inline: false,
+        }
+    }
+
+    /// Renders in the main screen rather than the alternate screen. The
+    /// terminal should have an inline viewport.
+    pub fn with_inline(mut self, inline: bool) -> Self {
+        self.inline = inline;
+        self
     }
 
     /// Initializes the terminal interface.
@@ -30,13 +44,16 @@ impl<B: Backend> Tui<B> {
     /// It enables the raw mode and sets terminal properties.
     pub fn init(&mut self) -> AppResult<()> {
         terminal::enable_raw_mode()?;
-        crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+        if !self.inline {
+            crossterm::execute!(io::stderr(), EnterAlternateScreen)?;
+        }
 
         // Define a custom panic hook to reset the terminal properties.
         // This way, you won't have your terminal messed up if an unexpected error happens.
+        let inline = self.inline;
         let panic_hook = panic::take_hook();
         panic::set_hook(Box::new(move |panic| {
-            Self::reset().expect("failed to reset the terminal");
+            Self::reset(inline).expect("failed to reset the terminal");
             panic_hook(panic);
         }));
 
@@ -60,9 +77,11 @@ impl<B: Backend> Tui<B> {
     ///
     /// This function is also used for the panic hook to revert
     /// the terminal properties if unexpected errors occur.
-    fn reset() -> AppResult<()> {
+    fn reset(inline: bool) -> AppResult<()> {
         terminal::disable_raw_mode()?;
-        crossterm::execute!(io::stderr(), LeaveAlternateScreen)?;
+        if !inline {
+            crossterm::execute!(io::stderr(), LeaveAlternateScreen)?;
+        }
         Ok(())
     }
 
@@ -70,8 +89,17 @@ impl<B: Backend> Tui<B> {
     ///
     /// It disables the raw mode and reverts back the terminal properties.
     pub fn exit(&mut self) -> AppResult<()> {
-        Self::reset()?;
-        self.terminal.show_cursor()?;
+        Self::reset(self.inline)?;
+        if self.inline {
+            // Continue below the last frame, which stays in the scrollback.
+            let bottom = self.terminal.get_frame().area().bottom();
+            self.terminal
+                .set_cursor_position((0, bottom.saturating_sub(1)))?;
+            self.terminal.show_cursor()?;
+            eprintln!();
+        } else {
+            self.terminal.show_cursor()?;
+        }
         Ok(())
     }
 }
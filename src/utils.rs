@@ -1,10 +1,31 @@
-use std::collections::{HashMap, VecDeque};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, VecDeque},
+    fmt::Write,
+    ops::Range,
+};
+
+use chrono::{
+    format::{Item, StrftimeItems},
+    DateTime, NaiveDate, NaiveTime,
+};
+use itertools::Itertools;
 
 use polars::{
-    datatypes::{AnyValue, DataType},
+    datatypes::{AnyValue, DataType, TimeUnit},
+    error::PolarsResult,
     frame::DataFrame,
+    lazy::{
+        dsl::{col, len},
+        frame::{pivot::pivot_stable, IntoLazy},
+    },
+    prelude::{DataFrameJoinOps, JoinArgs, JoinCoalesce, JoinType, NamedFrom, SortOptions},
     series::{ChunkCompare, Series},
 };
+use unicode_width::UnicodeWidthStr;
+
+/// Days from the first day of the common era to the Unix epoch.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
 
 #[derive(Debug)]
 pub struct TableValues {
@@ -14,10 +35,17 @@ pub struct TableValues {
 }
 
 impl TableValues {
-    pub fn from_dataframe(data_frame: &DataFrame) -> Self {
+    pub fn from_dataframe(data_frame: &DataFrame, format: &ValueFormat) -> Self {
         let height = data_frame.height();
         let width = data_frame.width();
-        let pool = data_frame.iter().map(Series::iter).round_robin().collect();
+        let mut pool = ValuePool::new(16, 16);
+        let formats = format.column_formats(data_frame);
+        data_frame
+            .iter()
+            .map(Series::iter)
+            .round_robin()
+            .enumerate()
+            .for_each(|(idx, value)| pool.push(&format.apply(value, formats[idx % width])));
         Self {
             pool,
             width,
@@ -43,18 +71,210 @@ impl TableValues {
         self.height
     }
 
-    pub fn replace_dataframe(&mut self, data_frame: &DataFrame) {
+    pub fn replace_dataframe(&mut self, data_frame: &DataFrame, format: &ValueFormat) {
         self.height = data_frame.height();
         self.width = data_frame.width();
         self.pool.clear();
+        let formats = format.column_formats(data_frame);
         data_frame
             .iter()
             .map(Series::iter)
             .round_robin()
-            .for_each(|value| self.pool.push(value));
+            .enumerate()
+            .for_each(|(idx, value)| {
+                self.pool
+                    .push(&format.apply(value, formats[idx % self.width]))
+            });
+    }
+
+    pub fn widths(&self, headers: &[String]) -> Vec<usize> {
+        headers
+            .iter()
+            .enumerate()
+            .map(|(col, header)| {
+                (0..self.height)
+                    .filter_map(|row| self.get(row, col))
+                    .map(|value| value.lines().next().map(str::width).unwrap_or(0))
+                    .max()
+                    .unwrap_or_default()
+                    .max(header.width())
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct ValueFormat {
+    pub thousands: bool,
+    pub bools: BoolGlyphs,
+    pub bases: HashMap<String, IntBase>,
+    pub date_format: Option<String>,
+    pub date_formats: HashMap<String, String>,
+}
+
+/// How boolean values are rendered in the table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum BoolGlyphs {
+    #[default]
+    Text,
+    Check,
+    Dot,
+}
+
+impl BoolGlyphs {
+    /// The next rendering in the `Text`, `Check`, `Dot` cycle.
+    pub fn next(self) -> Self {
+        match self {
+            BoolGlyphs::Text => BoolGlyphs::Check,
+            BoolGlyphs::Check => BoolGlyphs::Dot,
+            BoolGlyphs::Dot => BoolGlyphs::Text,
+        }
+    }
+
+    pub fn glyph(self, value: bool) -> &'static str {
+        match (self, value) {
+            (BoolGlyphs::Text, true) => "true",
+            (BoolGlyphs::Text, false) => "false",
+            (BoolGlyphs::Check, true) => "✓",
+            (BoolGlyphs::Check, false) => "✗",
+            (BoolGlyphs::Dot, true) => "●",
+            (BoolGlyphs::Dot, false) => "○",
+        }
+    }
+}
+
+/// Base the integers of a column are rendered in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum IntBase {
+    #[default]
+    Decimal,
+    Hex,
+    Binary,
+}
+
+impl IntBase {
+    /// The next base in the `Decimal`, `Hex`, `Binary` cycle.
+    pub fn next(self) -> Self {
+        match self {
+            IntBase::Decimal => IntBase::Hex,
+            IntBase::Hex => IntBase::Binary,
+            IntBase::Binary => IntBase::Decimal,
+        }
+    }
+
+    /// The integer in this base, negative ones in two's complement of their
+    /// width, or `None` for decimal and other values.
+    pub fn format(self, value: &AnyValue) -> Option<String> {
+        let bits = match *value {
+            AnyValue::Int8(value) => value as u8 as u64,
+            AnyValue::Int16(value) => value as u16 as u64,
+            AnyValue::Int32(value) => value as u32 as u64,
+            AnyValue::Int64(value) => value as u64,
+            AnyValue::UInt8(value) => value as u64,
+            AnyValue::UInt16(value) => value as u64,
+            AnyValue::UInt32(value) => value as u64,
+            AnyValue::UInt64(value) => value,
+            _ => return None,
+        };
+        match self {
+            IntBase::Decimal => None,
+            IntBase::Hex => Some(format!("{:#x}", bits)),
+            IntBase::Binary => Some(format!("{:#b}", bits)),
+        }
     }
 }
 
+impl std::fmt::Display for IntBase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IntBase::Decimal => f.pad("decimal"),
+            IntBase::Hex => f.pad("hex"),
+            IntBase::Binary => f.pad("binary"),
+        }
+    }
+}
+
+impl ValueFormat {
+    /// Base and date format of each column of the data frame, the format of
+    /// the column taking precedence over the one of all columns.
+    fn column_formats(&self, data_frame: &DataFrame) -> Vec<(IntBase, Option<&str>)> {
+        data_frame
+            .get_column_names()
+            .into_iter()
+            .map(|name| {
+                (
+                    self.bases.get(name).copied().unwrap_or_default(),
+                    self.date_formats
+                        .get(name)
+                        .or(self.date_format.as_ref())
+                        .map(String::as_str),
+                )
+            })
+            .collect()
+    }
+
+    pub fn apply(&self, value: AnyValue, (base, date_format): (IntBase, Option<&str>)) -> String {
+        if let Some(text) = base.format(&value) {
+            return text;
+        }
+        if let Some(text) = date_format.and_then(|format| format_temporal(&value, format)) {
+            return text;
+        }
+        match value {
+            AnyValue::Int8(_)
+            | AnyValue::Int16(_)
+            | AnyValue::Int32(_)
+            | AnyValue::Int64(_)
+            | AnyValue::UInt8(_)
+            | AnyValue::UInt16(_)
+            | AnyValue::UInt32(_)
+            | AnyValue::UInt64(_)
+                if self.thousands =>
+            {
+                group_thousands(&value.to_string())
+            }
+            AnyValue::Boolean(value) => self.bools.glyph(value).to_owned(),
+            _ => any_value_into_string(value),
+        }
+    }
+}
+
+/// The date, time, or datetime in the strftime format, e.g. `%d/%m/%Y`; none
+/// for other values, datetimes with a time zone, and formats with fields the
+/// value lacks, such as the hour of a date.
+pub fn format_temporal(value: &AnyValue, format: &str) -> Option<String> {
+    let mut text = String::new();
+    match *value {
+        AnyValue::Date(days) => {
+            let date = NaiveDate::from_num_days_from_ce_opt(days + UNIX_EPOCH_DAYS_FROM_CE)?;
+            write!(text, "{}", date.format(format))
+        }
+        AnyValue::Datetime(value, unit, time_zone) if time_zone.is_none() => {
+            let datetime = match unit {
+                TimeUnit::Nanoseconds => DateTime::from_timestamp_nanos(value),
+                TimeUnit::Microseconds => DateTime::from_timestamp_micros(value)?,
+                TimeUnit::Milliseconds => DateTime::from_timestamp_millis(value)?,
+            };
+            write!(text, "{}", datetime.naive_utc().format(format))
+        }
+        AnyValue::Time(nanos) => {
+            let time = NaiveTime::from_num_seconds_from_midnight_opt(
+                (nanos / 1_000_000_000) as u32,
+                (nanos % 1_000_000_000) as u32,
+            )?;
+            write!(text, "{}", time.format(format))
+        }
+        _ => return None,
+    }
+    .ok()?;
+    Some(text)
+}
+
+/// Whether the strftime format is valid, e.g. `%Y-%m-%d` but not `%Q`.
+pub fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
 #[derive(Debug)]
 struct ValuePool {
     pool: String,
@@ -69,8 +289,8 @@ impl ValuePool {
         Self { pool, indices }
     }
 
-    pub fn push(&mut self, value: AnyValue) {
-        self.pool.push_str(&any_value_into_string(value));
+    pub fn push(&mut self, value: &str) {
+        self.pool.push_str(value);
         self.indices.push(self.pool.len());
     }
 
@@ -91,7 +311,7 @@ impl<'a> FromIterator<AnyValue<'a>> for ValuePool {
     fn from_iter<T: IntoIterator<Item = AnyValue<'a>>>(iter: T) -> Self {
         let mut pool = ValuePool::new(16, 16);
         for value in iter {
-            pool.push(value);
+            pool.push(&any_value_into_string(value));
         }
         pool
     }
@@ -233,37 +453,143 @@ pub fn line_count(text: &str, width: usize) -> usize {
     line_count
 }
 
-pub fn data_frame_widths(df: &polars::frame::DataFrame) -> Vec<usize> {
-    df.get_column_names()
-        .into_iter()
-        .zip(df.get_columns())
-        .map(|(col, series)| col.len().max(series_width(series)))
-        .collect::<Vec<_>>()
-}
-
-pub fn series_width(series: &Series) -> usize {
-    series
-        .iter()
-        .map(|any_value| {
-            any_value_into_string(any_value)
-                .lines()
-                .next()
-                .map(str::len)
-                .unwrap_or(0)
-        })
-        .max()
-        .unwrap_or_default()
-}
-
 pub fn any_value_into_string(value: polars::datatypes::AnyValue) -> String {
     match value {
         AnyValue::Null => "".to_owned(),
         AnyValue::StringOwned(v) => v.to_string(),
         AnyValue::String(v) => v.to_string(),
+        AnyValue::List(_) | AnyValue::Struct(_, _, _) | AnyValue::StructOwned(_) => {
+            let mut json = String::new();
+            write_json(&value, None, &mut json);
+            json
+        }
         _ => value.to_string(),
     }
 }
 
+/// Same as [`any_value_into_string`], but with nested values pretty printed
+/// as indented JSON.
+pub fn any_value_into_pretty_string(value: polars::datatypes::AnyValue) -> String {
+    match value {
+        AnyValue::List(_) | AnyValue::Struct(_, _, _) | AnyValue::StructOwned(_) => {
+            let mut json = String::new();
+            write_json(&value, Some(0), &mut json);
+            json
+        }
+        _ => any_value_into_string(value),
+    }
+}
+
+/// Writes the value as JSON, compact without an indentation level or pretty
+/// printed from it.
+fn write_json(value: &AnyValue, indent: Option<usize>, out: &mut String) {
+    fn write_items<T>(
+        items: Vec<T>,
+        brackets: (char, char),
+        indent: Option<usize>,
+        out: &mut String,
+        mut write_item: impl FnMut(T, &mut String),
+    ) {
+        out.push(brackets.0);
+        let empty = items.is_empty();
+        for (idx, item) in items.into_iter().enumerate() {
+            if idx > 0 {
+                out.push(',');
+            }
+            if let Some(indent) = indent {
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent + 1));
+            }
+            write_item(item, out);
+        }
+        if let Some(indent) = indent.filter(|_| !empty) {
+            out.push('\n');
+            out.push_str(&"  ".repeat(indent));
+        }
+        out.push(brackets.1);
+    }
+
+    fn write_field(name: &str, value: &AnyValue, indent: Option<usize>, out: &mut String) {
+        write_json_string(name, out);
+        out.push_str(if indent.is_some() { ": " } else { ":" });
+        write_json(value, indent, out);
+    }
+
+    let inner = indent.map(|indent| indent + 1);
+    match value {
+        AnyValue::Null => out.push_str("null"),
+        AnyValue::Boolean(value) => out.push_str(if *value { "true" } else { "false" }),
+        AnyValue::Float32(value) if !value.is_finite() => out.push_str("null"),
+        AnyValue::Float64(value) if !value.is_finite() => out.push_str("null"),
+        AnyValue::Int8(_)
+        | AnyValue::Int16(_)
+        | AnyValue::Int32(_)
+        | AnyValue::Int64(_)
+        | AnyValue::UInt8(_)
+        | AnyValue::UInt16(_)
+        | AnyValue::UInt32(_)
+        | AnyValue::UInt64(_)
+        | AnyValue::Float32(_)
+        | AnyValue::Float64(_) => out.push_str(&value.to_string()),
+        AnyValue::String(value) => write_json_string(value, out),
+        AnyValue::StringOwned(value) => write_json_string(value, out),
+        AnyValue::List(series) => write_items(
+            series.iter().collect(),
+            ('[', ']'),
+            indent,
+            out,
+            |item, out| write_json(&item, inner, out),
+        ),
+        AnyValue::Struct(_, _, fields) => write_items(
+            value._iter_struct_av().zip(fields.iter()).collect(),
+            ('{', '}'),
+            indent,
+            out,
+            |(item, field), out| write_field(field.name(), &item, inner, out),
+        ),
+        AnyValue::StructOwned(payload) => write_items(
+            payload.0.iter().zip(payload.1.iter()).collect(),
+            ('{', '}'),
+            indent,
+            out,
+            |(item, field), out| write_field(field.name(), item, inner, out),
+        ),
+        _ => write_json_string(&value.to_string(), out),
+    }
+}
+
+fn write_json_string(value: &str, out: &mut String) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+pub fn group_thousands(number: &str) -> String {
+    let (sign, digits) = number
+        .strip_prefix('-')
+        .map(|digits| ("-", digits))
+        .unwrap_or(("", number));
+    let mut grouped = String::with_capacity(number.len() + digits.len() / 3);
+    grouped.push_str(sign);
+    for (idx, digit) in digits.chars().enumerate() {
+        if idx > 0 && (digits.len() - idx) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
 pub fn infer_schema_safe(data_frame: &mut DataFrame) {
     let dtypes = [
         DataType::Int64,
@@ -295,6 +621,831 @@ pub fn infer_schema_safe(data_frame: &mut DataFrame) {
         });
 }
 
+/// Parses the string columns, other than the skipped ones, whose values are
+/// all numbers and some grouped with the thousands separator, e.g. `1,234.56`,
+/// returning the names of the converted columns. The decimal separator is `,`
+/// if the thousands separator is `.`, and `.` otherwise.
+pub fn parse_thousands(data_frame: &mut DataFrame, separator: char, skip: &[&str]) -> Vec<String> {
+    let decimal = if separator == '.' { ',' } else { '.' };
+    let converted = data_frame
+        .get_columns()
+        .iter()
+        .filter(|series| !skip.contains(&series.name()))
+        .filter_map(|series| {
+            let mut grouped = false;
+            let numbers = series
+                .str()
+                .ok()?
+                .into_iter()
+                .map(|value| match value {
+                    Some(value) => {
+                        let (number, has_group) = strip_thousands(value, separator, decimal)?;
+                        grouped |= has_group || (decimal != '.' && number.contains('.'));
+                        Some(Some(number))
+                    }
+                    None => Some(None),
+                })
+                .collect::<Option<Vec<_>>>()?;
+            if !grouped {
+                return None;
+            }
+            let series = if numbers.iter().flatten().any(|number| number.contains('.')) {
+                let values = numbers
+                    .iter()
+                    .map(|number| number.as_ref().map(|number| number.parse::<f64>().ok()))
+                    .map(Option::flatten)
+                    .collect::<Vec<_>>();
+                Series::new(series.name(), values)
+            } else {
+                let values = numbers
+                    .iter()
+                    .map(|number| number.as_ref().map(|number| number.parse::<i64>().ok()))
+                    .map(Option::flatten)
+                    .collect::<Vec<_>>();
+                Series::new(series.name(), values)
+            };
+            (series.null_count() == numbers.iter().filter(|number| number.is_none()).count())
+                .then_some(series)
+        })
+        .collect::<Vec<_>>();
+    converted
+        .into_iter()
+        .map(|series| {
+            let name = series.name().to_owned();
+            data_frame.replace(&name, series).unwrap();
+            name
+        })
+        .collect()
+}
+
+/// Number in the value without its thousands separators and with `.` as its
+/// decimal separator, and whether it had any thousands separator; none if the
+/// value is not a number or its digits are not grouped by three.
+fn strip_thousands(value: &str, separator: char, decimal: char) -> Option<(String, bool)> {
+    let value = value.trim();
+    let (sign, unsigned) = match value.strip_prefix(['-', '+']) {
+        Some(unsigned) => (&value[..1], unsigned),
+        None => ("", value),
+    };
+    let (integer, fraction) = match unsigned.split_once(decimal) {
+        Some((integer, fraction)) => (integer, Some(fraction)),
+        None => (unsigned, None),
+    };
+    let groups = integer.split(separator).collect::<Vec<_>>();
+    let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+    let valid = groups.iter().all(|group| digits(group))
+        && (groups.len() == 1 || groups[0].len() <= 3)
+        && groups[1..].iter().all(|group| group.len() == 3)
+        && fraction.is_none_or(digits);
+    valid.then(|| {
+        let mut number = format!("{}{}", sign, groups.concat());
+        if let Some(fraction) = fraction {
+            number.push('.');
+            number.push_str(fraction);
+        }
+        (number, groups.len() > 1)
+    })
+}
+
+/// Data type from its short name, e.g. `i64`, `f64`, `str`, or `date`.
+pub fn parse_dtype(name: &str) -> Option<DataType> {
+    Some(match name.to_lowercase().as_str() {
+        "i8" => DataType::Int8,
+        "i16" => DataType::Int16,
+        "i32" => DataType::Int32,
+        "i64" | "int" => DataType::Int64,
+        "u8" => DataType::UInt8,
+        "u16" => DataType::UInt16,
+        "u32" => DataType::UInt32,
+        "u64" => DataType::UInt64,
+        "f32" => DataType::Float32,
+        "f64" | "float" => DataType::Float64,
+        "str" | "string" => DataType::String,
+        "bool" | "boolean" => DataType::Boolean,
+        "date" => DataType::Date,
+        "time" => DataType::Time,
+        "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+        _ => return None,
+    })
+}
+
+/// Casts the series, failing if any non-null value can not be represented in
+/// the data type rather than turning it into null (or truncating a float).
+pub fn cast_lossless(series: &Series, dtype: &DataType) -> Option<Series> {
+    series
+        .cast(dtype)
+        .ok()
+        .filter(|cast| cast.null_count() == series.null_count())
+        .filter(|cast| {
+            !series.dtype().is_float()
+                || dtype.is_float()
+                || !dtype.is_numeric()
+                || cast
+                    .cast(series.dtype())
+                    .ok()
+                    .and_then(|back| back.equals_missing(series).then_some(()))
+                    .is_some()
+        })
+}
+
+/// Casts every column of the data frame to the data type, leaving the ones
+/// that can not be cast unchanged. Returns the names of those columns too.
+pub fn cast_all(data_frame: &DataFrame, dtype: &DataType) -> (DataFrame, Vec<String>) {
+    let mut failed = Vec::new();
+    let columns = data_frame
+        .get_columns()
+        .iter()
+        .map(|series| {
+            cast_lossless(series, dtype).unwrap_or_else(|| {
+                failed.push(series.name().to_owned());
+                series.clone()
+            })
+        })
+        .collect_vec();
+    (
+        DataFrame::new(columns).unwrap_or_else(|_| data_frame.clone()),
+        failed,
+    )
+}
+
+/// Ranks of the values of the series, from 1 for the smallest or, when
+/// descending, the largest; ties share the lowest rank of their group, e.g.
+/// 1, 2, 2, 4, and nulls have none.
+pub fn rank_series(series: &Series, name: &str, descending: bool) -> PolarsResult<Series> {
+    let order = series.arg_sort(SortOptions {
+        descending,
+        nulls_last: true,
+        ..Default::default()
+    });
+    let mut ranks = vec![None; series.len()];
+    let mut previous = None;
+    let mut rank = 0;
+    for (position, idx) in order.into_no_null_iter().enumerate() {
+        let value = series.get(idx as usize)?;
+        if value.is_null() {
+            break;
+        }
+        if previous.as_ref() != Some(&value) {
+            rank = position as u32 + 1;
+        }
+        ranks[idx as usize] = Some(rank);
+        previous = Some(value);
+    }
+    Ok(Series::new(name, ranks))
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ListStyle {
+    /// One value per line.
+    Lines,
+    /// Comma separated values, with strings single-quoted for SQL `IN (...)` clauses.
+    Comma,
+}
+
+impl std::fmt::Display for ListStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListStyle::Lines => write!(f, "lines"),
+            ListStyle::Comma => write!(f, "comma"),
+        }
+    }
+}
+
+pub fn series_to_list(series: &Series, style: ListStyle) -> String {
+    match style {
+        ListStyle::Lines => series.iter().map(any_value_into_string).join("\n"),
+        ListStyle::Comma if series.dtype() == &DataType::String => series
+            .iter()
+            .map(|value| match value {
+                AnyValue::Null => "NULL".to_owned(),
+                _ => format!("'{}'", any_value_into_string(value).replace('\'', "''")),
+            })
+            .join(", "),
+        ListStyle::Comma => series
+            .iter()
+            .map(|value| match value {
+                AnyValue::Null => "NULL".to_owned(),
+                _ => any_value_into_string(value),
+            })
+            .join(", "),
+    }
+}
+
+/// Whether each value of the series satisfies the comparison (`==`, `!=`,
+/// `<`, `<=`, `>`, or `>=`) with the value, compared as numbers if the series
+/// is numeric and as text otherwise; nulls never do.
+pub fn compare_mask(series: &Series, op: &str, value: &str) -> Result<Vec<bool>, String> {
+    let test: fn(Ordering) -> bool = match op {
+        "==" | "=" => Ordering::is_eq,
+        "!=" => Ordering::is_ne,
+        "<" => Ordering::is_lt,
+        "<=" => Ordering::is_le,
+        ">" => Ordering::is_gt,
+        ">=" => Ordering::is_ge,
+        _ => Err(format!(
+            "Invalid operator '{}', expected ==, !=, <, <=, >, or >=",
+            op
+        ))?,
+    };
+    if series.dtype().is_numeric() {
+        let number = value
+            .parse::<f64>()
+            .map_err(|_| format!("'{}' is not a number", value))?;
+        let floats = series
+            .cast(&DataType::Float64)
+            .map_err(|err| err.to_string())?;
+        Ok(floats
+            .f64()
+            .map_err(|err| err.to_string())?
+            .iter()
+            .map(|float| {
+                float
+                    .and_then(|float| float.partial_cmp(&number))
+                    .is_some_and(test)
+            })
+            .collect())
+    } else {
+        Ok(series
+            .iter()
+            .map(|item| !item.is_null() && test(any_value_into_string(item).as_str().cmp(value)))
+            .collect())
+    }
+}
+
+/// Tab separated values of the data frame with a header row, quoting the
+/// fields with tabs, line breaks, or quotes the way spreadsheets paste them.
+pub fn data_frame_to_tsv(data_frame: &DataFrame) -> String {
+    fn field(text: String) -> String {
+        if text.contains(['\t', '\n', '\r', '"']) {
+            format!("\"{}\"", text.replace('"', "\"\""))
+        } else {
+            text
+        }
+    }
+    let columns = data_frame
+        .get_columns()
+        .iter()
+        .map(|series| {
+            series
+                .iter()
+                .map(|value| field(any_value_into_string(value)))
+                .collect_vec()
+        })
+        .collect_vec();
+    let mut tsv = data_frame
+        .get_column_names()
+        .into_iter()
+        .map(|name| field(name.to_owned()))
+        .join("\t");
+    for row in 0..data_frame.height() {
+        tsv.push('\n');
+        tsv.push_str(&columns.iter().map(|column| column[row].as_str()).join("\t"));
+    }
+    tsv
+}
+
+/// Ways of copying a single cell value to the clipboard.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum CopyFormat {
+    /// The value as shown in the sheet view.
+    Raw,
+    /// Single-quoted as an SQL literal, or `NULL`.
+    Quoted,
+    /// Encoded as a JSON value.
+    Json,
+}
+
+impl CopyFormat {
+    pub const ALL: [CopyFormat; 3] = [CopyFormat::Raw, CopyFormat::Quoted, CopyFormat::Json];
+
+    pub fn format(&self, value: AnyValue) -> String {
+        match (self, value) {
+            (CopyFormat::Raw, value) => any_value_into_string(value),
+            (CopyFormat::Quoted, AnyValue::Null) => "NULL".to_owned(),
+            (CopyFormat::Quoted, value) => {
+                format!("'{}'", any_value_into_string(value).replace('\'', "''"))
+            }
+            (CopyFormat::Json, value) => {
+                let mut json = String::new();
+                write_json(&value, None, &mut json);
+                json
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CopyFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CopyFormat::Raw => f.pad("raw"),
+            CopyFormat::Quoted => f.pad("quoted"),
+            CopyFormat::Json => f.pad("json"),
+        }
+    }
+}
+
+/// Sum of each numeric column of the data frame, blank for other columns.
+pub fn column_totals(data_frame: &DataFrame) -> Vec<String> {
+    data_frame
+        .get_columns()
+        .iter()
+        .map(|series| {
+            series
+                .dtype()
+                .is_numeric()
+                .then(|| series.sum_reduce().ok())
+                .flatten()
+                .map(|total| any_value_into_string(total.value().clone()))
+                .unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Summary statistics of the series: sum, mean, min, and max for numeric
+/// series, otherwise the count of non-null and unique values.
+pub fn series_summary(series: &Series) -> Vec<(&'static str, String)> {
+    if series.dtype().is_numeric() {
+        [
+            ("Sum", series.sum_reduce()),
+            ("Avg", Ok(series.mean_reduce())),
+            ("Min", series.min_reduce()),
+            ("Max", series.max_reduce()),
+        ]
+        .into_iter()
+        .filter_map(|(name, scalar)| {
+            Some((name, any_value_into_string(scalar.ok()?.value().clone())))
+        })
+        .collect()
+    } else {
+        [
+            ("Count", Ok(series.len() - series.null_count())),
+            ("Unique", series.n_unique()),
+        ]
+        .into_iter()
+        .filter_map(|(name, value)| Some((name, value.ok()?.to_string())))
+        .collect()
+    }
+}
+
+/// Position of each value of a numeric series between the minimum (0) and
+/// the maximum (1) of the series, or `None` if the series is not numeric.
+pub fn bar_fractions(series: &Series) -> Option<Vec<Option<f64>>> {
+    if !series.dtype().is_numeric() {
+        return None;
+    }
+    let values = series.cast(&DataType::Float64).ok()?;
+    let values = values.f64().ok()?;
+    let finite = values
+        .into_iter()
+        .flatten()
+        .filter(|value| value.is_finite());
+    let (min, max) = finite.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+        (min.min(value), max.max(value))
+    });
+    let range = max - min;
+    Some(
+        values
+            .into_iter()
+            .map(|value| {
+                value.filter(|value| value.is_finite()).map(|value| {
+                    if range > 0.0 {
+                        (value - min) / range
+                    } else {
+                        1.0
+                    }
+                })
+            })
+            .collect(),
+    )
+}
+
+/// Bar of `width` characters filled to the fraction in eighths of a
+/// character, or blank without a fraction.
+pub fn bar_text(fraction: Option<f64>, width: usize) -> String {
+    const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    let eighths = fraction
+        .map(|fraction| (fraction.clamp(0.0, 1.0) * (width * 8) as f64).round() as usize)
+        .unwrap_or_default();
+    format!("{:<width$}", "█".repeat(eighths / 8) + EIGHTHS[eighths % 8])
+}
+
+/// Number of missing values in the series: nulls, and NaNs for float series.
+pub fn missing_count(series: &Series) -> usize {
+    let nans = if series.dtype().is_float() {
+        series
+            .is_nan()
+            .map(|mask| mask.sum().unwrap_or_default() as usize)
+            .unwrap_or_default()
+    } else {
+        0
+    };
+    series.null_count() + nans
+}
+
+/// Compact share of missing values of the series, e.g. `3%∅` or `<1%∅`.
+pub fn missing_annotation(series: &Series) -> String {
+    let missing = missing_count(series);
+    match (missing * 100).checked_div(series.len()) {
+        Some(0) if missing > 0 => "<1%∅".to_owned(),
+        Some(percent) => format!("{}%∅", percent),
+        None => "0%∅".to_owned(),
+    }
+}
+
+/// Detects the separator used in the sample lines. Candidates appearing the
+/// same number of times on every line are preferred, followed by the ones
+/// appearing on the most lines. Occurrences inside double quotes are ignored.
+pub fn infer_separator_from_sample(lines: &[impl AsRef<str>]) -> Option<char> {
+    [',', '\t', ';', '|']
+        .into_iter()
+        .filter_map(|separator| {
+            let counts = lines
+                .iter()
+                .map(|line| {
+                    line.as_ref()
+                        .chars()
+                        .scan(false, |quoted, c| {
+                            if c == '"' {
+                                *quoted = !*quoted;
+                            }
+                            Some(!*quoted && c == separator)
+                        })
+                        .filter(|is_separator| *is_separator)
+                        .count()
+                })
+                .collect::<Vec<_>>();
+            let lines_with = counts.iter().filter(|count| **count > 0).count();
+            let consistent = counts.iter().all_equal();
+            (lines_with > 0).then_some(((consistent, lines_with, counts[0]), separator))
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, separator)| separator)
+}
+
+/// Case-insensitive subsequence match of `pattern` in `text`. Returns the
+/// number of skipped characters between the matched ones (lower is better),
+/// or `None` if `text` does not contain `pattern` as a subsequence.
+pub fn fuzzy_score(pattern: &str, text: &str) -> Option<usize> {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    let mut score = 0;
+    let mut started = false;
+    for pc in pattern.chars().flat_map(char::to_lowercase) {
+        loop {
+            let tc = text.next()?;
+            if tc == pc {
+                started = true;
+                break;
+            } else if started {
+                score += 1;
+            }
+        }
+    }
+    Some(score)
+}
+
+/// Index of the column called `name`, falling back to a case-insensitive match
+/// and then to the only column whose name starts with `name` ignoring case.
+/// Fails if none or several match.
+pub fn match_column(headers: &[String], name: &str) -> Result<usize, String> {
+    if let Some(idx) = headers.iter().position(|header| header == name) {
+        return Ok(idx);
+    }
+    let name = name.to_lowercase();
+    if let Some(idx) = headers
+        .iter()
+        .position(|header| header.to_lowercase() == name)
+    {
+        return Ok(idx);
+    }
+    let matches = headers
+        .iter()
+        .enumerate()
+        .filter(|(_, header)| header.to_lowercase().starts_with(&name))
+        .collect_vec();
+    match matches.as_slice() {
+        [] => Err(format!("No column matches '{}'", name)),
+        [(idx, _)] => Ok(*idx),
+        _ => Err(format!(
+            "Ambiguous column '{}': {}",
+            name,
+            matches.iter().map(|(_, header)| header.as_str()).join(", ")
+        )),
+    }
+}
+
+/// Recursively replaces struct columns with one column per field, named
+/// with the dotted path of the field (e.g. `address.city`).
+pub fn flatten_struct_columns(data_frame: DataFrame) -> PolarsResult<DataFrame> {
+    fn flatten(series: Series, columns: &mut Vec<Series>) -> PolarsResult<()> {
+        if matches!(series.dtype(), DataType::Struct(_)) {
+            for mut field in series.struct_()?.fields_as_series() {
+                let name = format!("{}.{}", series.name(), field.name());
+                field.rename(&name);
+                flatten(field, columns)?;
+            }
+        } else {
+            columns.push(series);
+        }
+        Ok(())
+    }
+    let mut columns = Vec::with_capacity(data_frame.width());
+    for series in data_frame.take_columns() {
+        flatten(series, &mut columns)?;
+    }
+    DataFrame::new(columns)
+}
+
+/// Groups the data frame by the `by` columns and aggregates the `column` in
+/// each group with `agg` (sum, mean, count, min, or max). Without a column,
+/// only `count` is allowed and counts the rows of each group. The result is
+/// sorted by the group columns.
+pub fn group_aggregate(
+    data_frame: &DataFrame,
+    by: &[String],
+    agg: &str,
+    column: Option<&str>,
+) -> Result<DataFrame, String> {
+    for name in by.iter().map(String::as_str).chain(column) {
+        if data_frame.column(name).is_err() {
+            return Err(format!("Column '{}' not found", name));
+        }
+    }
+    let expr = match (agg, column) {
+        ("count", None) => len().alias("count"),
+        ("count", Some(name)) => col(name).count(),
+        ("sum", Some(name)) => col(name).sum(),
+        ("mean", Some(name)) => col(name).mean(),
+        ("min", Some(name)) => col(name).min(),
+        ("max", Some(name)) => col(name).max(),
+        ("sum" | "mean" | "min" | "max", None) => {
+            return Err(format!("Aggregation '{}' requires a column", agg))
+        }
+        _ => {
+            return Err(format!(
+                "Invalid aggregation '{}', expected sum, mean, count, min, or max",
+                agg
+            ))
+        }
+    };
+    let expr = match column {
+        Some(name) => expr.alias(&format!("{}_{}", name, agg)),
+        None => expr,
+    };
+    data_frame
+        .clone()
+        .lazy()
+        .group_by(by.iter().map(|name| col(name)).collect_vec())
+        .agg([expr])
+        .sort(by, Default::default())
+        .collect()
+        .map_err(|err| err.to_string())
+}
+
+/// Reshapes the data frame into one row for each value of the index column
+/// and one column for each value of the columns column, holding the values
+/// column aggregated with the function.
+pub fn pivot_table(
+    data_frame: &DataFrame,
+    index: &str,
+    columns: &str,
+    values: &str,
+    agg: &str,
+) -> Result<DataFrame, String> {
+    for name in [index, columns, values] {
+        if data_frame.column(name).is_err() {
+            return Err(format!("Column '{}' not found", name));
+        }
+    }
+    // The values of each cell are referred to by the empty column name
+    let element = col("");
+    let expr = match agg {
+        "first" => element.first(),
+        "last" => element.last(),
+        "sum" => element.sum(),
+        "mean" => element.mean(),
+        "median" => element.median(),
+        "count" => element.count(),
+        "min" => element.min(),
+        "max" => element.max(),
+        _ => {
+            return Err(format!(
+            "Invalid aggregation '{}', expected first, last, sum, mean, median, count, min, or max",
+            agg
+        ))
+        }
+    };
+    pivot_stable(
+        data_frame,
+        [columns],
+        Some([index]),
+        Some([values]),
+        false,
+        Some(expr),
+        None,
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Rows of the data frame whose value in the column equals the value at the
+/// row, or whose value is null if that one is null.
+pub fn filter_equal(data_frame: &DataFrame, column: usize, row: usize) -> PolarsResult<DataFrame> {
+    let series = &data_frame[column];
+    let value = series.slice(row as i64, 1);
+    let mask = if value.null_count() > 0 {
+        series.is_null()
+    } else {
+        series.equal(&value)?
+    };
+    data_frame.filter(&mask)
+}
+
+/// Wraps the text into at most `lines` lines of `width` characters, ending
+/// the last line with `…` if the text does not fit.
+pub fn fit_text(text: &str, width: usize, lines: usize) -> String {
+    let mut chars = text.chars().collect_vec();
+    let capacity = width * lines;
+    if capacity == 0 || chars.len() <= width {
+        return text.to_owned();
+    }
+    if chars.len() > capacity {
+        chars.truncate(capacity - 1);
+        chars.push('…');
+    }
+    chars
+        .chunks(width)
+        .map(|line| line.iter().collect::<String>())
+        .join("\n")
+}
+
+/// Splits command arguments on whitespace, keeping double quoted arguments
+/// (e.g. `"Unnamed: 0"`) together.
+pub fn split_args(query: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut arg = String::new();
+    let mut quoted = false;
+    let mut started = false;
+    for c in query.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                started = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if started {
+                    args.push(std::mem::take(&mut arg));
+                    started = false;
+                }
+            }
+            c => {
+                arg.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        args.push(arg);
+    }
+    args
+}
+
+/// Stacks the named data frames vertically, failing if their columns or
+/// data types differ.
+pub fn concat_frames(frames: &[(String, &DataFrame)]) -> Result<DataFrame, String> {
+    let [(first_name, first), rest @ ..] = frames else {
+        Err("Nothing to concatenate")?
+    };
+    let mut result = (*first).clone();
+    for (name, df) in rest {
+        if df.schema() != first.schema() {
+            Err(format!(
+                "Schema of '{}' ({}) does not match the schema of '{}' ({})",
+                name,
+                df.iter()
+                    .map(|series| format!("{} {}", series.name(), series.dtype()))
+                    .join(", "),
+                first_name,
+                first
+                    .iter()
+                    .map(|series| format!("{} {}", series.name(), series.dtype()))
+                    .join(", ")
+            ))?
+        }
+        result.vstack_mut(df).map_err(|err| err.to_string())?;
+    }
+    result.as_single_chunk_par();
+    Ok(result)
+}
+
+/// Joins the named data frames on their key columns with an inner, left,
+/// right, or outer join. Other columns of the right data frame that clash
+/// with the left one are suffixed with its name.
+pub fn join_frames(
+    (left_name, left): (&str, &DataFrame),
+    (right_name, right): (&str, &DataFrame),
+    (left_key, right_key): (&str, &str),
+    how: &str,
+) -> Result<DataFrame, String> {
+    let how = match how {
+        "inner" => JoinType::Inner,
+        "left" => JoinType::Left,
+        "right" => JoinType::Right,
+        "outer" | "full" => JoinType::Full,
+        _ => Err(format!(
+            "Invalid join type '{}', expected inner, left, right, or outer",
+            how
+        ))?,
+    };
+    for (name, df, key) in [(left_name, left, left_key), (right_name, right, right_key)] {
+        if df.column(key).is_err() {
+            Err(format!("Column '{}' not found in '{}'", key, name))?
+        }
+    }
+    left.join(
+        right,
+        [left_key],
+        [right_key],
+        JoinArgs::new(how)
+            .with_coalesce(JoinCoalesce::CoalesceColumns)
+            .with_suffix(Some(format!("_{}", right_name))),
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Value being typed at the end of a filter expression, e.g. `'Pa` in
+/// `city == 'Pa`.
+#[derive(Debug, PartialEq, Eq)]
+pub struct FilterValue {
+    pub column: String,
+    pub partial: String,
+    pub quote: Option<char>,
+}
+
+/// Finds the column and the partially typed value of the comparison the
+/// filter expression ends with, if any.
+pub fn filter_value_at_end(expr: &str) -> Option<FilterValue> {
+    let mut open = None;
+    for (idx, c) in expr.char_indices() {
+        match open {
+            Some((quote, _)) if c == quote => open = None,
+            None if c == '\'' || c == '"' => open = Some((c, idx)),
+            _ => (),
+        }
+    }
+    let (head, partial, quote) = match open {
+        Some((quote, idx)) => (&expr[..idx], &expr[idx + 1..], Some(quote)),
+        None => {
+            let start = expr
+                .char_indices()
+                .rev()
+                .take_while(|(_, c)| c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))
+                .last()
+                .map(|(idx, _)| idx)
+                .unwrap_or(expr.len());
+            (&expr[..start], &expr[start..], None)
+        }
+    };
+    let head = head.trim_end();
+    let head = ["==", "!=", "<>", "<=", ">=", "=", "<", ">"]
+        .into_iter()
+        .find_map(|op| head.strip_suffix(op))?
+        .trim_end();
+    let column = if let Some(head) = head.strip_suffix('"') {
+        head.rsplit_once('"')?.1
+    } else {
+        let start = head
+            .char_indices()
+            .rev()
+            .take_while(|(_, c)| c.is_alphanumeric() || *c == '_')
+            .last()?
+            .0;
+        &head[start..]
+    };
+    (!column.is_empty()).then(|| FilterValue {
+        column: column.to_owned(),
+        partial: partial.to_owned(),
+        quote,
+    })
+}
+
+/// Sorted distinct values of the series, to complete filter values from.
+pub fn distinct_values(series: &Series) -> PolarsResult<Vec<String>> {
+    let values = series.unique()?.cast(&DataType::String)?;
+    Ok(values
+        .str()?
+        .into_iter()
+        .flatten()
+        .map(str::to_owned)
+        .sorted()
+        .collect_vec())
+}
+
+/// Range of the sorted values starting with the prefix.
+pub fn prefix_range(values: &[String], prefix: &str) -> Range<usize> {
+    let start = values.partition_point(|value| value.as_str() < prefix);
+    let len = values[start..].partition_point(|value| value.starts_with(prefix));
+    start..start + len
+}
+
 #[inline]
 pub fn as_ascii(c: char) -> Option<u8> {
     c.is_ascii().then_some(c as u8)
@@ -313,10 +1464,24 @@ mod tests {
         }
         .unwrap();
 
-        let vp = TableValues::from_dataframe(&df);
+        let vp = TableValues::from_dataframe(&df, &ValueFormat::default());
         println!("{:?}", vp.get(0, 0))
     }
 
+    #[test]
+    fn test_table_values_widths() {
+        let df = df! {
+            "city" => ["Zürich", "東京"],
+            "n" => [1, 22],
+        }
+        .unwrap();
+        let values = TableValues::from_dataframe(&df, &ValueFormat::default());
+        assert_eq!(
+            values.widths(&["city".to_owned(), "名前".to_owned()]),
+            vec![6, 4]
+        );
+    }
+
     #[test]
     fn test_round_robin() {
         let v1 = vec![1, 2, 3];
@@ -493,6 +1658,471 @@ mod tests {
         assert_eq!(line_count(text, 10), 1);
     }
 
+    #[test]
+    fn test_group_thousands() {
+        assert_eq!(group_thousands("0"), "0");
+        assert_eq!(group_thousands("123"), "123");
+        assert_eq!(group_thousands("1234"), "1,234");
+        assert_eq!(group_thousands("1234567"), "1,234,567");
+        assert_eq!(group_thousands("-123456"), "-123,456");
+        assert_eq!(group_thousands("-1234567"), "-1,234,567");
+    }
+
+    #[test]
+    fn test_value_format_thousands() {
+        let df = df! {
+            "ints" => [1234567_i64],
+            "floats" => [1234567.5],
+        }
+        .unwrap();
+        let format = ValueFormat {
+            thousands: true,
+            ..Default::default()
+        };
+        let tv = TableValues::from_dataframe(&df, &format);
+        assert_eq!(tv.get(0, 0), Some("1,234,567"));
+        assert_eq!(tv.get(0, 1), Some("1234567.5"));
+    }
+
+    #[test]
+    fn test_value_format_bools() {
+        let df = df! {
+            "bools" => [Some(true), Some(false), None],
+        }
+        .unwrap();
+        let tv = TableValues::from_dataframe(&df, &ValueFormat::default());
+        assert_eq!(tv.get_row(0), vec!["true"]);
+        assert_eq!(tv.get_row(1), vec!["false"]);
+        let format = ValueFormat {
+            bools: BoolGlyphs::Check,
+            ..Default::default()
+        };
+        let tv = TableValues::from_dataframe(&df, &format);
+        assert_eq!(tv.get(0, 0), Some("✓"));
+        assert_eq!(tv.get(1, 0), Some("✗"));
+        assert_eq!(tv.get(2, 0), Some(""));
+    }
+
+    #[test]
+    fn test_value_format_bases() {
+        let df = df! {
+            "flags" => [Some(10_i32), Some(-1), None],
+            "floats" => [1.5, 2.5, 3.5],
+        }
+        .unwrap();
+        let format = ValueFormat {
+            bases: HashMap::from([
+                ("flags".to_owned(), IntBase::Hex),
+                ("floats".to_owned(), IntBase::Hex),
+            ]),
+            ..Default::default()
+        };
+        let tv = TableValues::from_dataframe(&df, &format);
+        assert_eq!(tv.get_row(0), vec!["0xa", "1.5"]);
+        assert_eq!(tv.get_row(1), vec!["0xffffffff", "2.5"]);
+        assert_eq!(tv.get_row(2), vec!["", "3.5"]);
+        assert_eq!(IntBase::Binary.format(&AnyValue::Int8(5)).unwrap(), "0b101");
+        assert_eq!(IntBase::Decimal.format(&AnyValue::Int8(5)), None);
+    }
+
+    #[test]
+    fn test_series_to_list() {
+        let strings = Series::new("strings", [Some("a"), None, Some("it's")]);
+        assert_eq!(series_to_list(&strings, ListStyle::Lines), "a\n\nit's");
+        assert_eq!(
+            series_to_list(&strings, ListStyle::Comma),
+            "'a', NULL, 'it''s'"
+        );
+
+        let numbers = Series::new("numbers", [Some(1), None, Some(3)]);
+        assert_eq!(series_to_list(&numbers, ListStyle::Comma), "1, NULL, 3");
+    }
+
+    #[test]
+    fn test_compare_mask() {
+        let amounts = Series::new("amount", [Some(500), Some(1500), None, Some(1000)]);
+        assert_eq!(
+            compare_mask(&amounts, ">", "1000").unwrap(),
+            [false, true, false, false]
+        );
+        assert_eq!(
+            compare_mask(&amounts, "<=", "1000").unwrap(),
+            [true, false, false, true]
+        );
+        let cities = Series::new("city", [Some("Paris"), None, Some("Oslo")]);
+        assert_eq!(
+            compare_mask(&cities, "!=", "Paris").unwrap(),
+            [false, false, true]
+        );
+        assert!(compare_mask(&amounts, ">", "many").is_err());
+        assert!(compare_mask(&amounts, "~", "1").is_err());
+    }
+
+    #[test]
+    fn test_data_frame_to_tsv() {
+        let df = df! {
+            "name" => [Some("a\tb"), None, Some("say \"hi\"")],
+            "n" => [1, 2, 3],
+        }
+        .unwrap();
+        assert_eq!(
+            data_frame_to_tsv(&df),
+            "name\tn\n\"a\tb\"\t1\n\t2\n\"say \"\"hi\"\"\"\t3"
+        );
+    }
+
+    #[test]
+    fn test_copy_format() {
+        let value = AnyValue::String("it's \"ok\"");
+        assert_eq!(CopyFormat::Raw.format(value.clone()), "it's \"ok\"");
+        assert_eq!(CopyFormat::Quoted.format(value.clone()), "'it''s \"ok\"'");
+        assert_eq!(CopyFormat::Json.format(value), "\"it's \\\"ok\\\"\"");
+        assert_eq!(CopyFormat::Quoted.format(AnyValue::Null), "NULL");
+        assert_eq!(CopyFormat::Json.format(AnyValue::Null), "null");
+        assert_eq!(CopyFormat::Json.format(AnyValue::Int32(42)), "42");
+    }
+
+    #[test]
+    fn test_column_totals() {
+        let df = df! {
+            "name" => ["a", "b", "c"],
+            "amount" => [Some(10), None, Some(32)],
+            "rate" => [0.5, 0.25, 0.25],
+        }
+        .unwrap();
+        assert_eq!(column_totals(&df), ["", "42", "1.0"]);
+    }
+
+    #[test]
+    fn test_series_summary() {
+        let numbers = Series::new("numbers", [Some(1), Some(2), None, Some(5)]);
+        assert_eq!(
+            series_summary(&numbers),
+            vec![
+                ("Sum", "8".to_owned()),
+                ("Avg", "2.666667".to_owned()),
+                ("Min", "1".to_owned()),
+                ("Max", "5".to_owned()),
+            ]
+        );
+
+        let strings = Series::new("strings", [Some("a"), Some("b"), None, Some("a")]);
+        assert_eq!(
+            series_summary(&strings),
+            vec![("Count", "3".to_owned()), ("Unique", "3".to_owned())]
+        );
+    }
+
+    #[test]
+    fn test_infer_separator_from_sample() {
+        assert_eq!(
+            infer_separator_from_sample(&["a,b,c", "1,2,3", "4,5,6"]),
+            Some(',')
+        );
+        assert_eq!(
+            infer_separator_from_sample(&["a\tb,c", "1\t2,3", "4\t5"]),
+            Some('\t')
+        );
+        assert_eq!(
+            infer_separator_from_sample(&["name;note", "x;\"a, b, c\"", "y;\"d\""]),
+            Some(';')
+        );
+        assert_eq!(
+            infer_separator_from_sample(&["a|b", "1|2"]),
+            Some('|')
+        );
+        assert_eq!(infer_separator_from_sample(&["single", "column"]), None);
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+        assert_eq!(fuzzy_score("quit", ":quit"), Some(0));
+        assert_eq!(fuzzy_score("QT", ":quit"), Some(2));
+        assert_eq!(fuzzy_score("qr", ":query"), Some(2));
+        assert_eq!(fuzzy_score("xyz", ":query"), None);
+        assert!(fuzzy_score("tab", ":tabn") < fuzzy_score("tab", ":theme and b"));
+    }
+
+    #[test]
+    fn test_match_column() {
+        let headers = ["id", "Name", "name_en", "price", "price_usd"]
+            .map(ToOwned::to_owned)
+            .to_vec();
+        assert_eq!(match_column(&headers, "id"), Ok(0));
+        assert_eq!(match_column(&headers, "Name"), Ok(1));
+        assert_eq!(match_column(&headers, "name_"), Ok(2));
+        assert_eq!(match_column(&headers, "PRICE"), Ok(3));
+        assert!(match_column(&headers, "pri").is_err());
+        assert!(match_column(&headers, "n").is_err());
+        assert!(match_column(&headers, "qty").is_err());
+    }
+
+    #[test]
+    fn test_flatten_struct_columns() {
+        let city = Series::new("city", ["Paris", "Oslo"]);
+        let zip = Series::new("zip", [75001, 150]);
+        let geo = StructChunked::from_series("geo", &[Series::new("lat", [48.9, 59.9])])
+            .unwrap()
+            .into_series();
+        let address = StructChunked::from_series("address", &[city, zip, geo])
+            .unwrap()
+            .into_series();
+        let df = DataFrame::new(vec![Series::new("id", [1, 2]), address]).unwrap();
+        let df = flatten_struct_columns(df).unwrap();
+        assert_eq!(
+            df.get_column_names(),
+            ["id", "address.city", "address.zip", "address.geo.lat"]
+        );
+        assert_eq!(
+            df.column("address.city").unwrap(),
+            &Series::new("address.city", ["Paris", "Oslo"])
+        );
+    }
+
+    #[test]
+    fn test_missing_annotation() {
+        let ints = Series::new("ints", [Some(1), None, Some(3), None]);
+        assert_eq!(missing_count(&ints), 2);
+        assert_eq!(missing_annotation(&ints), "50%∅");
+        let floats = Series::new("floats", [Some(1.0), Some(f64::NAN), None]);
+        assert_eq!(missing_count(&floats), 2);
+        assert_eq!(missing_annotation(&floats), "66%∅");
+        let mut values = vec![Some("a"); 999];
+        values.push(None);
+        assert_eq!(missing_annotation(&Series::new("strs", values)), "<1%∅");
+        assert_eq!(missing_annotation(&Series::new("full", ["a"])), "0%∅");
+        assert_eq!(missing_annotation(&Series::new_empty("empty", &DataType::Int32)), "0%∅");
+    }
+
+    #[test]
+    fn test_group_aggregate() {
+        let df = df! {
+            "category" => ["b", "a", "b", "a", "b"],
+            "amount" => [1, 2, 3, 4, 5],
+        }
+        .unwrap();
+        let by = ["category".to_owned()];
+        let sum = group_aggregate(&df, &by, "sum", Some("amount")).unwrap();
+        assert_eq!(
+            sum,
+            df! {
+                "category" => ["a", "b"],
+                "amount_sum" => [6, 9],
+            }
+            .unwrap()
+        );
+        let count = group_aggregate(&df, &by, "count", None).unwrap();
+        assert_eq!(count.get_column_names(), ["category", "count"]);
+        assert_eq!(
+            count.column("count").unwrap().u32().unwrap().to_vec(),
+            [Some(2), Some(3)]
+        );
+        assert!(group_aggregate(&df, &by, "median", Some("amount")).is_err());
+        assert!(group_aggregate(&df, &by, "sum", None).is_err());
+        assert!(group_aggregate(&df, &by, "sum", Some("price")).is_err());
+        assert!(group_aggregate(&df, &["kind".to_owned()], "count", None).is_err());
+    }
+
+    #[test]
+    fn test_filter_equal() {
+        let df = df! {
+            "city" => [Some("Oslo"), None, Some("Rome"), Some("Oslo"), None],
+            "n" => [1, 2, 3, 4, 5],
+        }
+        .unwrap();
+        let oslo = filter_equal(&df, 0, 3).unwrap();
+        assert_eq!(oslo.column("n").unwrap(), &Series::new("n", [1, 4]));
+        let nulls = filter_equal(&df, 0, 1).unwrap();
+        assert_eq!(nulls.column("n").unwrap(), &Series::new("n", [2, 5]));
+        let three = filter_equal(&df, 1, 2).unwrap();
+        assert_eq!(three.column("city").unwrap(), &Series::new("city", ["Rome"]));
+    }
+
+    #[test]
+    fn test_fit_text() {
+        assert_eq!(fit_text("name", 8, 1), "name");
+        assert_eq!(fit_text("description", 8, 1), "descrip…");
+        assert_eq!(fit_text("description", 6, 2), "descri\nption");
+        assert_eq!(fit_text("long description", 6, 2), "long d\nescri…");
+        assert_eq!(fit_text("anything", 0, 2), "anything");
+    }
+
+    #[test]
+    fn test_cast_all() {
+        let df = df! {
+            "ints" => ["1", "2", "3"],
+            "floats" => ["1.5", "2", ""],
+            "names" => ["a", "b", "c"],
+        }
+        .unwrap();
+        let dtype = parse_dtype("f64").unwrap();
+        let (cast, failed) = cast_all(&df, &dtype);
+        assert_eq!(failed, ["floats", "names"]);
+        assert_eq!(cast.column("ints").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(cast.column("floats").unwrap().dtype(), &DataType::String);
+        assert_eq!(parse_dtype("I64"), Some(DataType::Int64));
+        assert_eq!(parse_dtype("decimal"), None);
+
+        let df = df! {
+            "whole" => [1.0, 2.0],
+            "fraction" => [1.0, 2.5],
+        }
+        .unwrap();
+        let (_, failed) = cast_all(&df, &DataType::Int64);
+        assert_eq!(failed, ["fraction"]);
+    }
+
+    #[test]
+    fn test_nested_values_as_json() {
+        let df = df! {
+            "id" => [1, 2],
+            "name" => ["a \"b\"", "c"],
+        }
+        .unwrap();
+        let structs = df.into_struct("person").into_series();
+        assert_eq!(
+            any_value_into_string(structs.get(0).unwrap()),
+            r#"{"id":1,"name":"a \"b\""}"#
+        );
+        assert_eq!(
+            any_value_into_pretty_string(structs.get(1).unwrap()),
+            "{\n  \"id\": 2,\n  \"name\": \"c\"\n}"
+        );
+        let lists = Series::new("list", [1, 2, 3])
+            .implode()
+            .unwrap()
+            .into_series();
+        assert_eq!(any_value_into_string(lists.get(0).unwrap()), "[1,2,3]");
+        assert_eq!(any_value_into_string(AnyValue::Float64(1.5)), "1.5");
+    }
+
+    #[test]
+    fn test_pivot_table() {
+        let df = df! {
+            "region" => ["north", "south", "north", "north"],
+            "year" => [2023, 2023, 2024, 2024],
+            "amount" => [1, 2, 3, 4],
+        }
+        .unwrap();
+        let pivot = pivot_table(&df, "region", "year", "amount", "sum").unwrap();
+        assert_eq!(
+            pivot,
+            df! {
+                "region" => ["north", "south"],
+                "2023" => [Some(1), Some(2)],
+                "2024" => [Some(7), None],
+            }
+            .unwrap()
+        );
+        assert!(pivot_table(&df, "region", "month", "amount", "sum").is_err());
+        assert!(pivot_table(&df, "region", "year", "amount", "mode").is_err());
+    }
+
+    #[test]
+    fn test_bar_fractions() {
+        let series = Series::new("n", [Some(10), None, Some(20), Some(15)]);
+        assert_eq!(
+            bar_fractions(&series),
+            Some(vec![Some(0.0), None, Some(1.0), Some(0.5)])
+        );
+        let constant = Series::new("n", [3.0, 3.0]);
+        assert_eq!(bar_fractions(&constant), Some(vec![Some(1.0), Some(1.0)]));
+        assert_eq!(bar_fractions(&Series::new("s", ["a"])), None);
+    }
+
+    #[test]
+    fn test_bar_text() {
+        assert_eq!(bar_text(Some(1.0), 4), "████");
+        assert_eq!(bar_text(Some(0.5), 4), "██  ");
+        assert_eq!(bar_text(Some(10.0 / 32.0), 4), "█▎  ");
+        assert_eq!(bar_text(Some(0.0), 4), "    ");
+        assert_eq!(bar_text(None, 4), "    ");
+    }
+
+    #[test]
+    fn test_concat_frames() {
+        let a = df! { "id" => [1, 2], "name" => ["a", "b"] }.unwrap();
+        let b = df! { "id" => [3], "name" => ["c"] }.unwrap();
+        let c = df! { "id" => ["4"], "name" => ["d"] }.unwrap();
+        let df = concat_frames(&[("a".to_owned(), &a), ("b".to_owned(), &b)]).unwrap();
+        assert_eq!(df.height(), 3);
+        assert_eq!(
+            df.column("name").unwrap(),
+            &Series::new("name", ["a", "b", "c"])
+        );
+        assert!(concat_frames(&[("a".to_owned(), &a), ("c".to_owned(), &c)])
+            .unwrap_err()
+            .contains("'c' (id str, name str)"));
+    }
+
+    #[test]
+    fn test_join_frames() {
+        let users = df! { "id" => [1, 2, 3], "name" => ["a", "b", "c"] }.unwrap();
+        let orders = df! { "user" => [1, 1, 4], "name" => ["x", "y", "z"] }.unwrap();
+        let join = |keys, how| join_frames(("users", &users), ("orders", &orders), keys, how);
+        let inner = join(("id", "user"), "inner").unwrap();
+        assert_eq!(inner.get_column_names(), ["id", "name", "name_orders"]);
+        assert_eq!(inner.height(), 2);
+        assert_eq!(join(("id", "user"), "left").unwrap().height(), 4);
+        let outer = join(("id", "user"), "outer").unwrap();
+        assert_eq!(outer.height(), 5);
+        assert_eq!(outer.column("id").unwrap().null_count(), 0);
+        assert_eq!(
+            join(("id", "uid"), "inner").unwrap_err(),
+            "Column 'uid' not found in 'orders'"
+        );
+        assert!(join(("id", "user"), "cross").is_err());
+    }
+
+    #[test]
+    fn test_filter_value_at_end() {
+        assert_eq!(
+            filter_value_at_end("price > 10 AND city == 'Pa"),
+            Some(FilterValue {
+                column: "city".to_owned(),
+                partial: "Pa".to_owned(),
+                quote: Some('\''),
+            })
+        );
+        assert_eq!(
+            filter_value_at_end(r#""home town" = "#),
+            Some(FilterValue {
+                column: "home town".to_owned(),
+                partial: "".to_owned(),
+                quote: None,
+            })
+        );
+        assert_eq!(
+            filter_value_at_end("rooms >= 3").map(|value| value.partial),
+            Some("3".to_owned())
+        );
+        assert_eq!(filter_value_at_end("city == 'Paris'"), None);
+        assert_eq!(filter_value_at_end("city"), None);
+    }
+
+    #[test]
+    fn test_value_completions() {
+        let series = Series::new("city", ["Paris", "Parma", "Rome", "Paris"]);
+        let values = distinct_values(&series).unwrap();
+        assert_eq!(values, vec!["Paris", "Parma", "Rome"]);
+        assert_eq!(prefix_range(&values, "Par"), 0..2);
+        assert_eq!(prefix_range(&values, "Rome"), 2..3);
+        assert_eq!(prefix_range(&values, ""), 0..3);
+        assert!(prefix_range(&values, "M").is_empty());
+    }
+
+    #[test]
+    fn test_split_args() {
+        assert_eq!(split_args(""), Vec::<String>::new());
+        assert_eq!(split_args(" col_1  name "), vec!["col_1", "name"]);
+        assert_eq!(
+            split_args(r#""Unnamed: 0" id"#),
+            vec!["Unnamed: 0".to_owned(), "id".to_owned()]
+        );
+        assert_eq!(split_args(r#"a """#), vec!["a", ""]);
+    }
+
     #[test]
     fn test_infer_schema_safe_basic() {
         let mut df = df! {
@@ -509,4 +2139,68 @@ mod tests {
         assert_eq!(df.column("dates").unwrap().dtype(), &DataType::Date);
         assert_eq!(df.column("strings").unwrap().dtype(), &DataType::String);
     }
+
+    #[test]
+    fn test_parse_thousands() {
+        let mut df = df! {
+            "amounts" => [Some("1,234.56"), Some("-12.5"), None],
+            "counts" => [Some("1,000"), Some("7"), Some("12,345,678")],
+            "plain" => [Some("12"), Some("34"), Some("56")],
+            "ungrouped" => [Some("1,23"), Some("4"), Some("5")],
+            "skipped" => [Some("1,000"), Some("2"), Some("3")],
+        }
+        .unwrap();
+        assert_eq!(
+            parse_thousands(&mut df, ',', &["skipped"]),
+            vec!["amounts", "counts"]
+        );
+        assert_eq!(
+            df.column("amounts").unwrap(),
+            &Series::new("amounts", [Some(1234.56), Some(-12.5), None])
+        );
+        assert_eq!(
+            df.column("counts").unwrap(),
+            &Series::new("counts", [1000_i64, 7, 12345678])
+        );
+        for name in ["plain", "ungrouped", "skipped"] {
+            assert_eq!(df.column(name).unwrap().dtype(), &DataType::String);
+        }
+
+        let mut df = df! { "prices" => ["1.234,5", "3,25"] }.unwrap();
+        assert_eq!(parse_thousands(&mut df, '.', &[]), vec!["prices"]);
+        assert_eq!(
+            df.column("prices").unwrap(),
+            &Series::new("prices", [1234.5, 3.25])
+        );
+    }
+
+    #[test]
+    fn test_format_temporal() {
+        let date = AnyValue::Date(19727);
+        assert_eq!(format_temporal(&date, "%d/%m/%Y").unwrap(), "05/01/2024");
+        assert_eq!(format_temporal(&date, "%H:%M"), None);
+        let datetime = AnyValue::Datetime(1_704_459_600_000, TimeUnit::Milliseconds, &None);
+        assert_eq!(
+            format_temporal(&datetime, "%d %b %Y %H:%M").unwrap(),
+            "05 Jan 2024 13:00"
+        );
+        let time = AnyValue::Time(49_510_000_000_000);
+        assert_eq!(format_temporal(&time, "%I:%M %p").unwrap(), "01:45 PM");
+        assert_eq!(format_temporal(&AnyValue::Int32(1), "%Y"), None);
+        assert!(is_valid_date_format("%Y-%m-%d %H:%M"));
+        assert!(!is_valid_date_format("%Q"));
+    }
+
+    #[test]
+    fn test_rank_series() {
+        let series = Series::new("score", [Some(20), None, Some(10), Some(20), Some(5)]);
+        assert_eq!(
+            rank_series(&series, "rank", false).unwrap(),
+            Series::new("rank", [Some(3_u32), None, Some(2), Some(3), Some(1)])
+        );
+        assert_eq!(
+            rank_series(&series, "rank", true).unwrap(),
+            Series::new("rank", [Some(1_u32), None, Some(3), Some(1), Some(4)])
+        );
+    }
 }
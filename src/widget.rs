@@ -1,4 +1,12 @@
-use ratatui::{layout::Rect, style::Style, widgets::StatefulWidget};
+use itertools::Itertools;
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListState, Paragraph, StatefulWidget, Widget, Wrap},
+};
+
+use crate::utils::{fuzzy_score, CopyFormat};
 
 #[derive(Debug)]
 pub struct PromptState {
@@ -14,6 +22,22 @@ impl PromptState {
         self
     }
 
+    pub fn input_str(&mut self, text: &str) -> &mut Self {
+        text.lines()
+            .map(str::trim_end)
+            .filter(|line| !line.is_empty())
+            .enumerate()
+            .for_each(|(idx, line)| {
+                if idx > 0 {
+                    self.input_char(' ');
+                }
+                line.chars().for_each(|c| {
+                    self.input_char(if c == '\t' { ' ' } else { c });
+                });
+            });
+        self
+    }
+
     pub fn delete(&mut self) -> &mut Self {
         if self.cursor.1 < self.chars[self.cursor.0].len() {
             self.chars[self.cursor.0].remove(self.cursor.1);
@@ -154,6 +178,548 @@ impl StatefulWidget for Prompt {
     }
 }
 
+#[derive(Debug)]
+pub struct CommandPaletteState {
+    items: Vec<PaletteItem>,
+    query: String,
+    matches: Vec<usize>,
+    list: ListState,
+}
+
+#[derive(Debug, Clone)]
+pub struct PaletteItem {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub description: &'static str,
+}
+
+impl CommandPaletteState {
+    pub fn new(items: Vec<PaletteItem>) -> Self {
+        let mut state = Self {
+            items,
+            query: String::new(),
+            matches: Vec::new(),
+            list: ListState::default(),
+        };
+        state.update_matches();
+        state
+    }
+
+    pub fn input_char(&mut self, character: char) -> &mut Self {
+        self.query.push(character);
+        self.update_matches();
+        self
+    }
+
+    pub fn delete_backward(&mut self) -> &mut Self {
+        self.query.pop();
+        self.update_matches();
+        self
+    }
+
+    pub fn select_up(&mut self) -> &mut Self {
+        self.list.select_previous();
+        self
+    }
+
+    pub fn select_down(&mut self) -> &mut Self {
+        if self.list.selected().unwrap_or_default() + 1 < self.matches.len() {
+            self.list.select_next();
+        }
+        self
+    }
+
+    pub fn selected(&self) -> Option<&PaletteItem> {
+        self.list
+            .selected()
+            .and_then(|idx| self.matches.get(idx))
+            .map(|idx| &self.items[*idx])
+    }
+
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    fn update_matches(&mut self) {
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, item)| {
+                fuzzy_score(
+                    &self.query,
+                    &format!("{} {}", item.name, item.description),
+                )
+                .map(|score| (score, idx))
+            })
+            .sorted()
+            .map(|(_, idx)| idx)
+            .collect();
+        self.list
+            .select((!self.matches.is_empty()).then_some(0));
+    }
+}
+
+pub struct CommandPalette {
+    style: Style,
+    input_style: Style,
+    highlight_style: Style,
+}
+
+impl CommandPalette {
+    pub fn new(style: Style, input_style: Style, highlight_style: Style) -> Self {
+        Self {
+            style,
+            input_style,
+            highlight_style,
+        }
+    }
+}
+
+impl StatefulWidget for CommandPalette {
+    type State = CommandPaletteState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+        let block = Block::new()
+            .title(" Commands ")
+            .borders(Borders::ALL)
+            .style(self.style);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [input, list] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(inner);
+        Line::raw(format!("> {}", state.query))
+            .style(self.input_style)
+            .render(input, buf);
+        StatefulWidget::render(
+            List::new(state.matches.iter().map(|idx| {
+                let item = &state.items[*idx];
+                format!("{:<24} {}", item.usage, item.description)
+            }))
+            .highlight_style(self.highlight_style),
+            list,
+            buf,
+            &mut state.list,
+        );
+    }
+}
+
+/// Cursor and selection over the characters of a single value, used to copy
+/// part of a cell.
+#[derive(Debug)]
+pub struct TextSelectState {
+    title: String,
+    chars: Vec<char>,
+    cursor: usize,
+    anchor: Option<usize>,
+}
+
+impl TextSelectState {
+    pub fn new(title: impl Into<String>, text: &str) -> Self {
+        Self {
+            title: title.into(),
+            chars: text.chars().collect(),
+            cursor: 0,
+            anchor: None,
+        }
+    }
+
+    pub fn move_left(&mut self) -> &mut Self {
+        self.cursor = self.cursor.saturating_sub(1);
+        self
+    }
+
+    pub fn move_right(&mut self) -> &mut Self {
+        self.cursor = (self.cursor + 1).min(self.chars.len().saturating_sub(1));
+        self
+    }
+
+    pub fn move_home(&mut self) -> &mut Self {
+        self.cursor = 0;
+        self
+    }
+
+    pub fn move_end(&mut self) -> &mut Self {
+        self.cursor = self.chars.len().saturating_sub(1);
+        self
+    }
+
+    /// Moves to the start of the previous word.
+    pub fn move_word_left(&mut self) -> &mut Self {
+        while self.cursor > 0 && !self.chars[self.cursor - 1].is_alphanumeric() {
+            self.cursor -= 1;
+        }
+        while self.cursor > 0 && self.chars[self.cursor - 1].is_alphanumeric() {
+            self.cursor -= 1;
+        }
+        self
+    }
+
+    /// Moves to the end of the current or next word.
+    pub fn move_word_right(&mut self) -> &mut Self {
+        let last = self.chars.len().saturating_sub(1);
+        while self.cursor < last && !self.chars[self.cursor + 1].is_alphanumeric() {
+            self.cursor += 1;
+        }
+        while self.cursor < last && self.chars[self.cursor + 1].is_alphanumeric() {
+            self.cursor += 1;
+        }
+        self
+    }
+
+    /// Starts a selection at the cursor, or clears the current one.
+    pub fn toggle_anchor(&mut self) -> &mut Self {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+        self
+    }
+
+    /// Inclusive range of the selected characters, if a selection is started.
+    pub fn selection(&self) -> Option<(usize, usize)> {
+        self.anchor
+            .map(|anchor| (anchor.min(self.cursor), anchor.max(self.cursor)))
+    }
+
+    /// The selected text, or the whole text if no selection is started.
+    pub fn selected_text(&self) -> String {
+        match self.selection() {
+            Some((start, end)) if !self.chars.is_empty() => {
+                self.chars[start..=end].iter().collect()
+            }
+            _ => self.chars.iter().collect(),
+        }
+    }
+}
+
+pub struct TextSelect {
+    style: Style,
+    selection_style: Style,
+}
+
+impl TextSelect {
+    pub fn new(style: Style, selection_style: Style) -> Self {
+        Self {
+            style,
+            selection_style,
+        }
+    }
+}
+
+impl StatefulWidget for TextSelect {
+    type State = TextSelectState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+        let block = Block::new()
+            .title(format!(" {} ", state.title))
+            .title_bottom(" v select  y copy  esc close ")
+            .borders(Borders::ALL)
+            .style(self.style);
+        let (start, end) = state.selection().unwrap_or((state.cursor, state.cursor));
+        let spans = state
+            .chars
+            .iter()
+            .enumerate()
+            .map(|(idx, c)| {
+                let span = Span::raw(c.to_string());
+                if idx == state.cursor {
+                    span.reversed()
+                } else if (start..=end).contains(&idx) {
+                    span.style(self.selection_style)
+                } else {
+                    span
+                }
+            })
+            .collect_vec();
+        Paragraph::new(Line::from(spans))
+            .wrap(Wrap { trim: false })
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+/// Word-wrapped message that can be scrolled, used for errors too long for
+/// the status bar.
+/// Formats the focused cell can be copied as, each shown with its result.
+#[derive(Debug)]
+pub struct CopyMenuState {
+    title: String,
+    items: Vec<(CopyFormat, String)>,
+    list: ListState,
+}
+
+impl CopyMenuState {
+    pub fn new(title: impl Into<String>, items: Vec<(CopyFormat, String)>) -> Self {
+        Self {
+            title: title.into(),
+            items,
+            list: ListState::default().with_selected(Some(0)),
+        }
+    }
+
+    pub fn select_up(&mut self) -> &mut Self {
+        self.list.select_previous();
+        self
+    }
+
+    pub fn select_down(&mut self) -> &mut Self {
+        if self.list.selected().unwrap_or_default() + 1 < self.items.len() {
+            self.list.select_next();
+        }
+        self
+    }
+
+    pub fn selected(&self) -> Option<&(CopyFormat, String)> {
+        self.list.selected().and_then(|idx| self.items.get(idx))
+    }
+}
+
+pub struct CopyMenu {
+    style: Style,
+    highlight_style: Style,
+}
+
+impl CopyMenu {
+    pub fn new(style: Style, highlight_style: Style) -> Self {
+        Self {
+            style,
+            highlight_style,
+        }
+    }
+}
+
+impl StatefulWidget for CopyMenu {
+    type State = CopyMenuState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let [area] = Layout::horizontal([Constraint::Percentage(60)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(state.items.len() as u16 + 2)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+        let block = Block::new()
+            .title(format!(" Copy {} as ", state.title))
+            .title_bottom(" j/k move  y copy  esc close ")
+            .borders(Borders::ALL)
+            .style(self.style);
+        StatefulWidget::render(
+            List::new(state.items.iter().map(|(format, value)| {
+                format!("{:<8} {}", format, value.lines().next().unwrap_or_default())
+            }))
+            .highlight_style(self.highlight_style)
+            .block(block),
+            area,
+            buf,
+            &mut state.list,
+        );
+    }
+}
+
+#[derive(Debug)]
+pub struct MessagePopupState {
+    title: String,
+    message: String,
+    scroll: usize,
+}
+
+impl MessagePopupState {
+    pub fn new(title: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            message: message.into(),
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_up(&mut self) -> &mut Self {
+        self.scroll = self.scroll.saturating_sub(1);
+        self
+    }
+
+    pub fn scroll_down(&mut self) -> &mut Self {
+        self.scroll = self.scroll.saturating_add(1);
+        self
+    }
+}
+
+pub struct MessagePopup {
+    style: Style,
+}
+
+impl MessagePopup {
+    pub fn new(style: Style) -> Self {
+        Self { style }
+    }
+}
+
+impl StatefulWidget for MessagePopup {
+    type State = MessagePopupState;
+
+    fn render(
+        self,
+        area: ratatui::prelude::Rect,
+        buf: &mut ratatui::prelude::Buffer,
+        state: &mut Self::State,
+    ) {
+        let [area] = Layout::horizontal([Constraint::Percentage(80)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Percentage(60)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        Clear.render(area, buf);
+        let block = Block::new()
+            .title(format!(" {} ", state.title))
+            .title_bottom(" j/k scroll  any other key to close ")
+            .borders(Borders::ALL)
+            .style(self.style);
+        let inner = block.inner(area);
+        let width = (inner.width as usize).max(1);
+        let lines = state
+            .message
+            .lines()
+            .map(|line| line.chars().count().div_ceil(width).max(1))
+            .sum::<usize>();
+        state.scroll = state
+            .scroll
+            .min(lines.saturating_sub(inner.height as usize));
+        Paragraph::new(state.message.as_str())
+            .wrap(Wrap { trim: false })
+            .scroll((state.scroll as u16, 0))
+            .block(block)
+            .render(area, buf);
+    }
+}
+
+/// Values the filter expression can be completed with, shown above the
+/// status bar.
+pub struct Completions<'a> {
+    title: &'a str,
+    values: &'a [String],
+    style: Style,
+}
+
+impl<'a> Completions<'a> {
+    pub fn new(title: &'a str, values: &'a [String], style: Style) -> Self {
+        Self {
+            title,
+            values,
+            style,
+        }
+    }
+}
+
+impl Widget for Completions<'_> {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let width = self
+            .values
+            .iter()
+            .map(|value| value.chars().count())
+            .chain([self.title.chars().count()])
+            .max()
+            .unwrap_or_default() as u16
+            + 4;
+        let height = self.values.len() as u16 + 2;
+        let area = Rect {
+            x: area.x,
+            y: area.bottom().saturating_sub(height).max(area.y),
+            width: width.min(area.width),
+            height: height.min(area.height),
+        };
+        Clear.render(area, buf);
+        Widget::render(
+            List::new(self.values.iter().map(String::as_str))
+                .block(
+                    Block::new()
+                        .title(format!(" {} ", self.title))
+                        .borders(Borders::ALL),
+                )
+                .style(self.style),
+            area,
+            buf,
+        );
+    }
+}
+
+/// Screen shown when no tab is open, with the ways to open one.
+pub struct Welcome {
+    style: Style,
+    key_style: Style,
+}
+
+impl Welcome {
+    pub fn new(style: Style, key_style: Style) -> Self {
+        Self { style, key_style }
+    }
+}
+
+impl Widget for Welcome {
+    fn render(self, area: Rect, buf: &mut ratatui::prelude::Buffer) {
+        let keys = [
+            (":open <path>", "Open a file in a new tab"),
+            (":help", "List the commands and their usage"),
+            ("q", "Quit"),
+        ];
+        let lines = [Line::from("No file is open"), Line::default()]
+            .into_iter()
+            .chain(keys.into_iter().map(|(key, description)| {
+                Line::from(vec![
+                    Span::styled(format!("{:<14}", key), self.key_style),
+                    Span::raw(description),
+                ])
+            }))
+            .chain([
+                Line::default(),
+                Line::from("Usage: tw [options] <file(s)> or tw --stdin -f <format>"),
+            ])
+            .collect_vec();
+        let width = lines.iter().map(Line::width).max().unwrap_or_default() as u16;
+        let [area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        let [area] = Layout::vertical([Constraint::Length(lines.len() as u16)])
+            .flex(ratatui::layout::Flex::Center)
+            .areas(area);
+        Paragraph::new(lines)
+            .style(self.style)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -168,4 +734,66 @@ mod tests {
         state.input_char('r');
         assert_eq!(state.command(), "char")
     }
+
+    #[test]
+    fn command_palette_state_test() {
+        let mut state = CommandPaletteState::new(vec![
+            PaletteItem {
+                name: ":quit",
+                usage: ":q",
+                description: "Quit Tabiew",
+            },
+            PaletteItem {
+                name: ":query",
+                usage: ":Q <query>",
+                description: "Query the data",
+            },
+        ]);
+        assert_eq!(state.selected().unwrap().name, ":quit");
+        state.input_char('q').input_char('r');
+        assert_eq!(state.selected().unwrap().name, ":query");
+        state.input_char('z');
+        assert!(state.selected().is_none());
+        state.delete_backward().delete_backward().select_down();
+        assert_eq!(state.selected().unwrap().name, ":query");
+    }
+
+    #[test]
+    fn copy_menu_state_test() {
+        let mut state = CopyMenuState::new(
+            "name",
+            vec![
+                (CopyFormat::Raw, "a".to_owned()),
+                (CopyFormat::Quoted, "'a'".to_owned()),
+            ],
+        );
+        assert_eq!(state.selected().unwrap().0, CopyFormat::Raw);
+        state.select_down().select_down();
+        assert_eq!(state.selected().unwrap().0, CopyFormat::Quoted);
+        state.select_up().select_up();
+        assert_eq!(state.selected().unwrap().0, CopyFormat::Raw);
+    }
+
+    #[test]
+    fn text_select_state_test() {
+        let mut state = TextSelectState::new("log", "error id=ab12 at 10:00");
+        assert_eq!(state.selected_text(), "error id=ab12 at 10:00");
+        state.move_word_right().move_word_right().move_word_right();
+        state.move_word_left().toggle_anchor().move_word_right();
+        assert_eq!(state.selected_text(), "ab12");
+        state.toggle_anchor().move_end().toggle_anchor().move_left().move_left();
+        assert_eq!(state.selected_text(), ":00");
+        state.move_home().toggle_anchor().toggle_anchor();
+        assert_eq!(state.selected_text(), "e");
+        let mut empty = TextSelectState::new("empty", "");
+        empty.move_right().move_word_right().toggle_anchor();
+        assert_eq!(empty.selected_text(), "");
+    }
+
+    #[test]
+    fn input_str_test() {
+        let mut state = PromptState::from(vec![":Q ".to_owned()]);
+        state.input_str("SELECT *\r\nFROM df\n\nWHERE\ta > 1\n");
+        assert_eq!(state.command(), ":Q SELECT * FROM df WHERE a > 1")
+    }
 }